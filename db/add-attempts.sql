@@ -0,0 +1,15 @@
+-- add multiple attempts per game to an existing database
+CREATE TYPE attempt_aggregation AS ENUM ('best', 'sum', 'average');
+
+ALTER TABLE games
+    ADD COLUMN attempts integer NOT NULL DEFAULT 1,
+    ADD COLUMN aggregation attempt_aggregation NOT NULL DEFAULT 'best';
+
+CREATE TABLE attempts (
+    game_id uuid NOT NULL,
+    team_id uuid NOT NULL,
+    number integer NOT NULL,
+    data text NOT NULL,
+    CONSTRAINT attempts_pkey PRIMARY KEY (game_id, team_id, number),
+    FOREIGN KEY (game_id, team_id) REFERENCES game_team (game_id, team_id) ON UPDATE CASCADE ON DELETE CASCADE
+);
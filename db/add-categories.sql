@@ -0,0 +1,22 @@
+-- add categories to an existing database, every existing team is assigned to the category of its gender
+ALTER TYPE subject_type ADD VALUE 'category';
+
+CREATE TABLE categories (
+    id uuid PRIMARY KEY NOT NULL,
+    name varchar (31) NOT NULL,
+    position integer NOT NULL DEFAULT 0,
+    year integer NOT NULL,
+    UNIQUE (year, name)
+);
+
+INSERT INTO categories (id, name, position, year)
+SELECT gen_random_uuid(), initcap(gender::text), array_position(enum_range(NULL::team_gender), gender) - 1, year
+FROM (SELECT DISTINCT gender, year FROM teams) AS existing;
+
+ALTER TABLE teams ADD COLUMN category_id uuid REFERENCES categories (id);
+
+UPDATE teams SET category_id = categories.id
+FROM categories
+WHERE categories.year = teams.year AND categories.name = initcap(teams.gender::text);
+
+ALTER TABLE teams ALTER COLUMN category_id SET NOT NULL;
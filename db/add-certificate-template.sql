@@ -0,0 +1,3 @@
+-- add configurable certificate texts to an existing database, NULL uses the built-in template
+ALTER TABLE trophy_config
+    ADD COLUMN certificate_template text;
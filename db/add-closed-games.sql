@@ -0,0 +1,6 @@
+-- add closing games to an existing database
+-- games that are already complete are closed right away, so a running year isn't suddenly pending again
+ALTER TABLE games
+    ADD COLUMN closed boolean NOT NULL DEFAULT false;
+UPDATE games SET closed = true
+    WHERE NOT EXISTS (SELECT 1 FROM game_team WHERE game_team.game_id = games.id AND data IS NULL AND status = 'ok');
@@ -0,0 +1,4 @@
+-- add the distance-, lowest-points- and hits-with-time-kinds to an existing database
+ALTER TYPE game_kind ADD VALUE IF NOT EXISTS 'distance';
+ALTER TYPE game_kind ADD VALUE IF NOT EXISTS 'lowest_points';
+ALTER TYPE game_kind ADD VALUE IF NOT EXISTS 'hits_with_time';
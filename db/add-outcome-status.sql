@@ -0,0 +1,11 @@
+-- add statuses and penalties for outcomes to an existing database
+CREATE TYPE outcome_status AS ENUM ('ok', 'dns', 'dnf', 'dsq');
+CREATE TYPE non_finisher_scoring AS ENUM ('zero', 'last');
+
+ALTER TABLE game_team
+    ADD COLUMN status outcome_status NOT NULL DEFAULT 'ok',
+    ADD COLUMN penalty_points integer DEFAULT NULL,
+    ADD COLUMN penalty_millis bigint DEFAULT NULL;
+
+ALTER TABLE trophy_config
+    ADD COLUMN non_finishers non_finisher_scoring NOT NULL DEFAULT 'zero';
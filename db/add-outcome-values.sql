@@ -0,0 +1,6 @@
+-- store the typed value of an outcome next to its raw data in an existing database
+-- existing data is only parsed when it's written again, evaluation keeps reading the raw data
+ALTER TABLE game_team
+    ADD COLUMN value_integer integer DEFAULT NULL,
+    ADD COLUMN value_millis bigint DEFAULT NULL,
+    ADD COLUMN value_millimetres bigint DEFAULT NULL;
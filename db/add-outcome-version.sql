@@ -0,0 +1,3 @@
+-- add a version to outcomes in an existing database, so concurrent updates can be detected
+ALTER TABLE game_team
+    ADD COLUMN version integer NOT NULL DEFAULT 0;
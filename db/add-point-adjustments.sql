@@ -0,0 +1,10 @@
+-- add point adjustments to an existing database
+ALTER TYPE subject_type ADD VALUE 'adjustment';
+
+CREATE TABLE point_adjustments (
+    id uuid PRIMARY KEY NOT NULL,
+    team_id uuid NOT NULL REFERENCES teams (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    game_id uuid REFERENCES games (id) ON DELETE SET NULL,
+    amount integer NOT NULL,
+    reason text NOT NULL
+);
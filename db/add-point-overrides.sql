@@ -0,0 +1,3 @@
+-- remember point values set by hand in an existing database, so evaluations keep them
+ALTER TABLE game_team
+    ADD COLUMN override_reason text DEFAULT NULL;
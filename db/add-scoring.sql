@@ -0,0 +1,5 @@
+-- add the per-game scoring-strategy to an existing database
+CREATE TYPE scoring_kind AS ENUM ('descending', 'linear', 'formula', 'podium', 'percentage');
+
+ALTER TABLE games
+ADD COLUMN scoring scoring_kind NOT NULL DEFAULT 'descending';
@@ -0,0 +1,6 @@
+-- add configurable tie-break rules to an existing database
+CREATE TYPE tie_breaker AS ENUM ('wins', 'best_placement', 'game');
+
+ALTER TABLE trophy_config
+    ADD COLUMN tie_breakers tie_breaker [] NOT NULL DEFAULT '{wins, best_placement, game}',
+    ADD COLUMN tiebreaker_game_id uuid REFERENCES games (id) ON DELETE SET NULL;
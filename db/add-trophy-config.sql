@@ -0,0 +1,9 @@
+-- add the per-year trophy-configuration to an existing database
+CREATE TYPE place_gap AS ENUM ('skip', 'dense');
+
+CREATE TABLE trophy_config (
+    year integer PRIMARY KEY NOT NULL,
+    max_points integer NOT NULL DEFAULT 50,
+    min_points integer NOT NULL DEFAULT 1,
+    gap place_gap NOT NULL DEFAULT 'skip'
+);
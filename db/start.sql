@@ -1,11 +1,21 @@
 -- drop existing tables
+DROP TABLE IF EXISTS point_adjustments;
+DROP TABLE IF EXISTS attempts;
 DROP TABLE IF EXISTS game_team;
 DROP TABLE IF EXISTS transaction_history;
 DROP TABLE IF EXISTS users;
 DROP TABLE IF EXISTS games;
 DROP TABLE IF EXISTS teams;
+DROP TABLE IF EXISTS categories;
+DROP TABLE IF EXISTS trophy_config;
 -- drop existing enums
 DROP TYPE IF EXISTS game_kind;
+DROP TYPE IF EXISTS scoring_kind;
+DROP TYPE IF EXISTS attempt_aggregation;
+DROP TYPE IF EXISTS place_gap;
+DROP TYPE IF EXISTS tie_breaker;
+DROP TYPE IF EXISTS outcome_status;
+DROP TYPE IF EXISTS non_finisher_scoring;
 DROP TYPE IF EXISTS team_gender;
 DROP TYPE IF EXISTS user_role;
 DROP TYPE IF EXISTS log_level;
@@ -14,17 +24,27 @@ DROP TYPE IF EXISTS subject_type;
 ---
 ---
 -- create enums
-CREATE TYPE game_kind AS ENUM ('points', 'time');
+CREATE TYPE game_kind AS ENUM ('points', 'time', 'distance', 'lowest_points', 'hits_with_time');
+CREATE TYPE scoring_kind AS ENUM ('descending', 'linear', 'formula', 'podium', 'percentage');
+CREATE TYPE attempt_aggregation AS ENUM ('best', 'sum', 'average');
+CREATE TYPE place_gap AS ENUM ('skip', 'dense');
+CREATE TYPE tie_breaker AS ENUM ('wins', 'best_placement', 'game');
+CREATE TYPE outcome_status AS ENUM ('ok', 'dns', 'dnf', 'dsq');
+CREATE TYPE non_finisher_scoring AS ENUM ('zero', 'last');
 CREATE TYPE team_gender AS ENUM ('female', 'male', 'mixed');
 CREATE TYPE user_role AS ENUM ('admin', 'referee', 'visualizer');
 CREATE TYPE log_level AS ENUM ('debug', 'info', 'warn');
-CREATE TYPE subject_type AS ENUM ('game', 'team', 'outcome', 'history', 'user', 'eval', 'general');
+CREATE TYPE subject_type AS ENUM ('game', 'team', 'outcome', 'history', 'user', 'eval', 'general', 'category', 'adjustment');
 -- create model-tables
 CREATE TABLE games (
     id uuid PRIMARY KEY NOT NULL,
     trophy_id integer NOT NULL,
     name varchar (50) NOT NULL,
     kind game_kind NOT NULL,
+    scoring scoring_kind NOT NULL DEFAULT 'descending',
+    attempts integer NOT NULL DEFAULT 1,
+    aggregation attempt_aggregation NOT NULL DEFAULT 'best',
+    closed boolean NOT NULL DEFAULT false,
     year integer NOT NULL
 );
 CREATE TABLE users (
@@ -35,11 +55,20 @@ CREATE TABLE users (
     session uuid DEFAULT NULL,
     game_id uuid REFERENCES games (id)
 );
+CREATE TABLE categories (
+    id uuid PRIMARY KEY NOT NULL,
+    -- sheet-names in the exported workbook are limited to 31 characters
+    name varchar (31) NOT NULL,
+    position integer NOT NULL DEFAULT 0,
+    year integer NOT NULL,
+    UNIQUE (year, name)
+);
 CREATE TABLE teams (
     id uuid PRIMARY KEY NOT NULL,
     trophy_id integer NOT NULL,
     name varchar (50) NOT NULL,
     gender team_gender NOT NULL,
+    category_id uuid NOT NULL REFERENCES categories (id),
     points integer NOT NULL DEFAULT 0,
     year integer NOT NULL
 );
@@ -47,9 +76,49 @@ CREATE TABLE game_team (
     game_id uuid REFERENCES games (id) ON UPDATE CASCADE ON DELETE CASCADE,
     team_id uuid REFERENCES teams (id) ON UPDATE CASCADE ON DELETE CASCADE,
     data text DEFAULT NULL,
+    -- the typed value of data, depending on the kind of the game
+    value_integer integer DEFAULT NULL,
+    value_millis bigint DEFAULT NULL,
+    value_millimetres bigint DEFAULT NULL,
+    -- teams that didn't start, didn't finish or were disqualified need no data
+    status outcome_status NOT NULL DEFAULT 'ok',
+    penalty_points integer DEFAULT NULL,
+    penalty_millis bigint DEFAULT NULL,
     point_value int DEFAULT NULL,
+    -- set if an admin has overridden the point value, evaluations keep it until the data changes
+    override_reason text DEFAULT NULL,
+    -- incremented on every change by a user, see optimistic concurrency in README.md
+    version integer NOT NULL DEFAULT 0,
     CONSTRAINT game_team_pkey PRIMARY KEY (game_id, team_id) -- explicit pk
 );
+-- the single attempts of games with more than one, their aggregate is stored in game_team
+CREATE TABLE attempts (
+    game_id uuid NOT NULL,
+    team_id uuid NOT NULL,
+    number integer NOT NULL,
+    data text NOT NULL,
+    CONSTRAINT attempts_pkey PRIMARY KEY (game_id, team_id, number),
+    FOREIGN KEY (game_id, team_id) REFERENCES game_team (game_id, team_id) ON UPDATE CASCADE ON DELETE CASCADE
+);
+-- points awarded or deducted by the jury outside of the games, e.g. after protests
+CREATE TABLE point_adjustments (
+    id uuid PRIMARY KEY NOT NULL,
+    team_id uuid NOT NULL REFERENCES teams (id) ON UPDATE CASCADE ON DELETE CASCADE,
+    game_id uuid REFERENCES games (id) ON DELETE SET NULL,
+    amount integer NOT NULL,
+    reason text NOT NULL
+);
+CREATE TABLE trophy_config (
+    year integer PRIMARY KEY NOT NULL,
+    max_points integer NOT NULL DEFAULT 50,
+    min_points integer NOT NULL DEFAULT 1,
+    gap place_gap NOT NULL DEFAULT 'skip',
+    tie_breakers tie_breaker [] NOT NULL DEFAULT '{wins, best_placement, game}',
+    tiebreaker_game_id uuid REFERENCES games (id) ON DELETE SET NULL,
+    non_finishers non_finisher_scoring NOT NULL DEFAULT 'zero',
+    -- NULL uses the built-in template
+    certificate_template text
+);
 --- create meta-tables
 CREATE TABLE transaction_history (
     id uuid PRIMARY KEY NOT NULL,
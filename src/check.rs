@@ -0,0 +1,232 @@
+use serde::Serialize;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::{
+    ApiResult,
+    model::{CustomError, Game, GameKind, Outcome, ParsedOutcome, Team, Value},
+};
+
+/// Values further away from the middle half of a category than this many times its spread are reported as outliers.
+/// 3 corresponds to Tukey's "far out" fences.
+const OUTLIER_FACTOR: f64 = 3.0;
+
+/// Outlier-detection needs a few values to tell what's normal.
+const OUTLIER_MIN_VALUES: usize = 4;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemKind {
+    MissingData,
+    Unparsable,
+    NegativeTime,
+    Outlier,
+    DuplicateTrophyId,
+}
+
+/// A single thing that would make the evaluation fail or look suspicious.
+#[derive(Serialize)]
+pub struct Problem {
+    pub kind: ProblemKind,
+    pub game_id: Option<Uuid>,
+    pub team_id: Option<Uuid>,
+    pub message: String,
+}
+
+/// The result of checking a year before evaluating it - an empty list of problems means the evaluation can go ahead.
+#[derive(Serialize)]
+pub struct CheckReport {
+    pub year: i32,
+    pub problems: Vec<Problem>,
+}
+
+/// Walk all outcomes of the specified year like the evaluation would, but collect every problem instead of stopping at the first.
+/// Nothing is written.
+pub async fn check_trophy(pool: &PgPool, year: i32) -> ApiResult<CheckReport> {
+    let teams = Team::find_all(pool, year).await?.0;
+    let mut problems = duplicate_trophy_ids(&teams);
+
+    let mut connection = pool.acquire().await?;
+    for game in Game::find_all(&mut connection, year).await?.0 {
+        let mut categories = HashMap::<Uuid, Vec<ParsedOutcome>>::new();
+
+        for (outcome, parsed_outcome) in
+            Outcome::parse_each_for_game(&game, &mut connection).await?
+        {
+            match parsed_outcome {
+                Ok(parsed_outcome) if parsed_outcome.value.is_some() => categories
+                    .entry(parsed_outcome.team.category_id)
+                    .or_default()
+                    .push(parsed_outcome),
+                Ok(_) => {}
+                Err(CustomError::EarlyEvaluationError { .. }) => problems.push(Problem {
+                    kind: ProblemKind::MissingData,
+                    game_id: Some(game.id),
+                    team_id: Some(outcome.team_id),
+                    message: format!("{} has no data for game {}.", outcome.team_name, game.name),
+                }),
+                Err(err) => {
+                    let data = outcome.data.clone().unwrap_or_default();
+                    // negative times can't be parsed, but they deserve a clearer message
+                    let kind = match game.kind {
+                        GameKind::Time if data.trim().starts_with('-') => ProblemKind::NegativeTime,
+                        _ => ProblemKind::Unparsable,
+                    };
+                    problems.push(Problem {
+                        kind,
+                        game_id: Some(game.id),
+                        team_id: Some(outcome.team_id),
+                        message: format!(
+                            "The data \"{}\" of team {} for game {} is invalid: {}",
+                            data, outcome.team_name, game.name, err
+                        ),
+                    });
+                }
+            }
+        }
+
+        for outcomes in categories.values() {
+            for outcome in outliers(outcomes) {
+                problems.push(Problem {
+                    kind: ProblemKind::Outlier,
+                    game_id: Some(game.id),
+                    team_id: Some(outcome.team.id),
+                    message: format!(
+                        "The value {} of team {} for game {} differs a lot from the other teams.",
+                        outcome.value.as_ref().map_or(0.0, Value::as_f64),
+                        outcome.team.name,
+                        game.name
+                    ),
+                });
+            }
+        }
+    }
+
+    Ok(CheckReport { year, problems })
+}
+
+fn duplicate_trophy_ids(teams: &[Team]) -> Vec<Problem> {
+    let mut by_trophy_id = HashMap::<i32, Vec<&Team>>::new();
+    for team in teams {
+        by_trophy_id.entry(team.trophy_id).or_default().push(team);
+    }
+
+    let mut problems = Vec::<Problem>::new();
+    for (trophy_id, teams) in by_trophy_id {
+        if teams.len() < 2 {
+            continue;
+        }
+
+        for team in &teams {
+            problems.push(Problem {
+                kind: ProblemKind::DuplicateTrophyId,
+                game_id: None,
+                team_id: Some(team.id),
+                message: format!(
+                    "{} shares the trophy-id {} with {} other team(s).",
+                    team.name,
+                    trophy_id,
+                    teams.len() - 1
+                ),
+            });
+        }
+    }
+
+    problems
+}
+
+/// Find the outcomes outside of the fences `Q1 - k * IQR` and `Q3 + k * IQR`, with k being [OUTLIER_FACTOR].
+fn outliers(outcomes: &[ParsedOutcome]) -> Vec<&ParsedOutcome> {
+    if outcomes.len() < OUTLIER_MIN_VALUES {
+        return Vec::new();
+    }
+
+    let mut values: Vec<f64> = outcomes
+        .iter()
+        .filter_map(|o| o.value.as_ref().map(Value::as_f64))
+        .collect();
+    values.sort_by(f64::total_cmp);
+
+    let first_quartile = quantile(&values, 0.25);
+    let third_quartile = quantile(&values, 0.75);
+    let spread = (third_quartile - first_quartile) * OUTLIER_FACTOR;
+
+    outcomes
+        .iter()
+        .filter(|o| {
+            o.value.as_ref().map(Value::as_f64).is_some_and(|value| {
+                value < first_quartile - spread || value > third_quartile + spread
+            })
+        })
+        .collect()
+}
+
+/// Linearly interpolated quantile of already sorted values.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{OutcomeStatus, TeamGender};
+
+    fn get_team(trophy_id: i32) -> Team {
+        Team {
+            id: Uuid::now_v7(),
+            trophy_id,
+            name: format!("Team {}", trophy_id),
+            gender: TeamGender::Female,
+            category_id: Uuid::nil(),
+            points: 0,
+            year: 2024,
+        }
+    }
+
+    fn get_outcomes(points: &[i32]) -> Vec<ParsedOutcome> {
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, points)| ParsedOutcome {
+                game_id: Uuid::nil(),
+                team: get_team(i as i32),
+                status: OutcomeStatus::Ok,
+                value: Some(Value::Points(*points)),
+                point_value: None,
+                point_override: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn outliers_far_out() {
+        let outcomes = get_outcomes(&[10, 12, 11, 13, 9, 500]);
+        let found: Vec<i32> = outliers(&outcomes)
+            .iter()
+            .map(|o| o.team.trophy_id)
+            .collect();
+        assert_eq!(found, vec![5]);
+    }
+
+    #[test]
+    fn outliers_need_enough_values() {
+        let outcomes = get_outcomes(&[10, 12, 500]);
+        assert!(outliers(&outcomes).is_empty());
+    }
+
+    #[test]
+    fn duplicate_trophy_ids_reports_every_team() {
+        let teams = vec![get_team(1), get_team(1), get_team(2)];
+        let problems = duplicate_trophy_ids(&teams);
+        assert_eq!(problems.len(), 2);
+        assert!(
+            problems
+                .iter()
+                .all(|p| p.kind == ProblemKind::DuplicateTrophyId)
+        );
+    }
+}
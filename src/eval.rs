@@ -1,57 +1,171 @@
 use crate::{
-    ApiResult, TypeInfo,
-    model::{CustomError, Game, GenderOutcomes, Outcome, ParsedOutcome, Team, Value},
+    ApiResult,
+    model::{
+        Category, CategoryOutcomes, CategoryTeams, CustomError, Game, NonFinisherScoring, Outcome,
+        OutcomeStatus, OutcomeVec, ParsedOutcome, PlaceGap, PointAdjustment, PointChange,
+        PointChangeVec, Team, TrophyConfig,
+    },
+    ranking::{Ranked, rank},
+    scoring::{Placement, ScoringStrategy},
 };
-use actix_files::NamedFile;
-use sqlx::PgPool;
-use std::{
-    cmp::Reverse,
-    fmt::{self, Display},
-    fs::File,
-    time::SystemTime,
-};
-use xlsxwriter::*;
+use serde::Serialize;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
 
-pub struct ResultFile(pub NamedFile);
+/// The point values and team totals an evaluation would assign.
+#[derive(Serialize)]
+pub struct Preview {
+    pub year: i32,
+    pub outcomes: Vec<PreviewOutcome>,
+    pub teams: Vec<PointChange>,
+}
 
-const MAX_POINTS: i32 = 50;
+#[derive(Serialize)]
+pub struct PreviewOutcome {
+    pub game_id: Uuid,
+    pub team_id: Uuid,
+    pub team_name: String,
+    pub point_value: i32,
+}
 
 /// Checks whether all games have finished.
-pub async fn is_trophy_done(pool: &PgPool, year: i32) -> ApiResult<bool> {
-    let pending_games = Game::find_all_pending(year, pool).await?.0;
+pub async fn is_trophy_done(connection: &mut PgConnection, year: i32) -> ApiResult<bool> {
+    let pending_games = Game::find_all_pending(year, connection).await?.0;
     Ok(pending_games == 0)
 }
 
-/// Checks whether all teams have points assigned.
-pub async fn is_evaluated(pool: &PgPool, year: i32) -> ApiResult<bool> {
-    let teams = Team::find_all(pool, year).await?.0;
-    Ok(teams.iter().all(|team| team.points != 0))
+/// Checks whether all outcomes have point values assigned.
+pub async fn is_evaluated(connection: &mut PgConnection, year: i32) -> ApiResult<bool> {
+    let unevaluated_games = Game::find_all_unevaluated(year, connection).await?.0;
+    Ok(unevaluated_games == 0)
 }
 
+/// Evaluate the trophy for the first time.
+/// Errors if it has already been evaluated, use [recompute_trophy] to correct results afterwards.
 pub async fn evaluate_trophy(pool: &PgPool, year: i32) -> ApiResult<()> {
-    if !is_trophy_done(pool, year).await? {
+    let mut tx = pool.begin().await?;
+    if is_evaluated(&mut tx, year).await? {
         return Err(CustomError::EarlyEvaluationError {
-            message: "Tried to evaluate while teams are still playing!".to_string(),
+            message: "Already evaluated.".to_string(),
         });
     }
 
-    if is_evaluated(pool, year).await? {
+    recompute(&mut tx, year).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Assign all point values and team totals of the specified year from scratch.
+/// This is safe to call repeatedly - e.g. after correcting an outcome.
+/// Returns the teams whose totals have changed.
+pub async fn recompute_trophy(pool: &PgPool, year: i32) -> ApiResult<PointChangeVec> {
+    let mut tx = pool.begin().await?;
+    let changes = recompute(&mut tx, year).await?;
+    tx.commit().await?;
+    Ok(changes)
+}
+
+/// Show the point values and team totals an evaluation would assign, without persisting anything.
+/// This scores outcomes exactly like [recompute_trophy] does.
+pub async fn preview_trophy(pool: &PgPool, year: i32) -> ApiResult<Preview> {
+    let mut connection = pool.acquire().await?;
+    let scored = score_trophy(&mut connection, year).await?;
+
+    // adjustments are part of the totals, just like in Team::recompute_points
+    let mut totals = PointAdjustment::totals(year, &mut connection).await?;
+    let mut outcomes = Vec::<PreviewOutcome>::new();
+    for outcome in scored {
+        // evaluate() always assigns a point value
+        let point_value = outcome.point_value.unwrap_or_default();
+        *totals.entry(outcome.team.id).or_default() += point_value;
+        outcomes.push(PreviewOutcome {
+            game_id: outcome.game_id,
+            team_id: outcome.team.id,
+            team_name: outcome.team.name,
+            point_value,
+        });
+    }
+
+    let teams = Team::find_all(pool, year)
+        .await?
+        .0
+        .into_iter()
+        .map(|team| PointChange {
+            points: totals.get(&team.id).copied().unwrap_or_default(),
+            previous_points: team.points,
+            team_id: team.id,
+            trophy_id: team.trophy_id,
+            name: team.name,
+        })
+        .collect();
+
+    Ok(Preview {
+        year,
+        outcomes,
+        teams,
+    })
+}
+
+/// Evaluate a single finished [Game] while others may still be running and update the team totals.
+/// Calling this again overwrites the previous point values of the game.
+pub async fn evaluate_single_game(id: Uuid, pool: &PgPool) -> ApiResult<OutcomeVec> {
+    let game = Game::find(id, pool).await?;
+    let year = game.year;
+
+    let mut tx = pool.begin().await?;
+    let config = TrophyConfig::find(year, &mut tx).await?;
+    let mut outcomes = Vec::<Outcome>::new();
+    for outcome in score_game(game, &config, &mut tx).await? {
+        outcomes.push(Outcome::set_point_value(outcome, &mut tx).await?);
+    }
+    Team::recompute_points(year, &mut tx).await?;
+    tx.commit().await?;
+
+    Ok(OutcomeVec(outcomes))
+}
+
+/// Runs as part of the caller's transaction, which must be rolled back on errors.
+/// Otherwise, a year could end up half-evaluated.
+async fn recompute(connection: &mut PgConnection, year: i32) -> ApiResult<PointChangeVec> {
+    let scored = score_trophy(connection, year).await?;
+
+    // totals are derived from the point values instead of being added up, which keeps this idempotent
+    Outcome::reset_point_values(year, connection).await?;
+    for outcome in scored {
+        Outcome::set_point_value(outcome, connection).await?;
+    }
+
+    Team::recompute_points(year, connection).await
+}
+
+/// Score all outcomes of the specified year without persisting anything.
+/// Errors if the trophy is not yet done or any outcome can't be parsed.
+async fn score_trophy(connection: &mut PgConnection, year: i32) -> ApiResult<Vec<ParsedOutcome>> {
+    if !is_trophy_done(connection, year).await? {
         return Err(CustomError::EarlyEvaluationError {
-            message: "Already evaluated.".to_string(),
+            message: "Tried to evaluate while teams are still playing!".to_string(),
         });
     }
 
-    // I cannot use locked here, as locked might be changed arbitrarily by admins(me)
-    for game in Game::find_all(pool, year).await?.0 {
-        evaluate_game(game, pool).await?;
+    let config = TrophyConfig::find(year, connection).await?;
+
+    // all games are closed by now, but admins may still have changed outcomes afterwards, so every game is checked again
+    let mut scored = Vec::<ParsedOutcome>::new();
+    for game in Game::find_all(connection, year).await?.0 {
+        scored.extend(score_game(game, &config, connection).await?);
     }
-    Ok(())
+
+    Ok(scored)
 }
 
-/// Evalutate the given [Game].
+/// Evalutate the given [Game] and return its [ParsedOutcome]s with their point values assigned.
 /// Errors if the [Game] is not yet done.
-async fn evaluate_game(game: Game, pool: &PgPool) -> ApiResult<()> {
-    if game.is_pending(pool).await? {
+async fn score_game(
+    game: Game,
+    config: &TrophyConfig,
+    connection: &mut PgConnection,
+) -> ApiResult<Vec<ParsedOutcome>> {
+    if game.is_pending(connection).await? {
         return Err(CustomError::EarlyEvaluationError {
             message: format!(
                 "Tried to evaluate game {} while the game is not done.",
@@ -60,142 +174,173 @@ async fn evaluate_game(game: Game, pool: &PgPool) -> ApiResult<()> {
         });
     }
 
-    // get all Outcomes as ParsedOutcomes for game separated by gender
-    let GenderOutcomes {
-        male_outcomes,
-        female_outcomes,
-    } = Outcome::parse_by_gender_for_game(&game, pool).await?;
+    // get all Outcomes as ParsedOutcomes for game separated by category
+    let CategoryOutcomes(categories) =
+        Outcome::parse_by_category_for_game(&game, connection).await?;
 
-    // persist all changes from evaluate()
-    // -> update_points and set_point_value write the current values of team and outcome (that have been assigned by evaluate) to the database
-    for outcome in evaluate(female_outcomes) {
-        outcome.team.update_points(pool).await?;
-        Outcome::set_point_value(outcome, pool).await?;
-    }
+    let strategy = game.scoring.strategy();
 
-    for outcome in evaluate(male_outcomes) {
-        outcome.team.update_points(pool).await?;
-        Outcome::set_point_value(outcome, pool).await?;
-    }
-
-    Ok(())
+    // each category is ranked on its own
+    Ok(categories
+        .into_values()
+        .flat_map(|outcomes| evaluate(outcomes, strategy, config))
+        .collect())
 }
 
 /// Evaluate a [Game] by its [ParsedOutcome]s.
+/// Outcomes are ranked by their value, the points for each place are then assigned by the given [ScoringStrategy].
+/// Unless the strategy uses a fixed table, no outcome receives less than [TrophyConfig::min_points], so large years never end up with zero or negative points.
+/// Teams that didn't finish follow after all others, their points depend on [TrophyConfig::non_finishers].
 /// For examples of how this algorithm performs evaluation, see the tests or README.
-fn evaluate(mut outcomes: Vec<ParsedOutcome>) -> Vec<ParsedOutcome> {
+fn evaluate(
+    mut outcomes: Vec<ParsedOutcome>,
+    strategy: &dyn ScoringStrategy,
+    config: &TrophyConfig,
+) -> Vec<ParsedOutcome> {
     // sanity-check to ensure there is data
     if outcomes.is_empty() {
         return outcomes;
     }
 
-    // NOTE with this algorithm, we might assign negative points if there's ever more than 50 teams of a single gender.
-    // This could be circumvented by having MAX_POINTS be the number of teams - or simply not decreasing current_points below 1.
-    // Reaching this scenario is unlikely since the trophy is currently capped at 50 to 60 teams.
-    let mut current_points = MAX_POINTS;
-    let mut current_gap = 1;
-
-    match outcomes[0].value {
-        Value::Seconds(_) => outcomes.sort_by(|a, b| a.value.cmp(&b.value)),
-        // sort_by sorts in ascending order by default, but since points require descending order, we have to reverse
-        Value::Points(_) => outcomes.sort_by(|a, b| a.value.cmp(&b.value).reverse()),
-    }
+    let places = places(&mut outcomes, &config.gap);
+    let participants = outcomes.len() as i32;
+    let best = outcomes[0].value.clone();
 
     // NOTE I've decided against using iter() and map() - this was causing more hassle than good here.
-    for i in 0..outcomes.len() {
+    for (i, place) in places.into_iter().enumerate() {
+        // point values set by hand are kept, the team still takes its place in the ranking
+        let points = match outcomes[i].point_override {
+            Some(points) => points,
+            None => match (outcomes[i].status, config.non_finishers) {
+                (OutcomeStatus::Ok, _)
+                | (OutcomeStatus::Dns | OutcomeStatus::Dnf, NonFinisherScoring::Last) => {
+                    let points = strategy.points(
+                        &Placement {
+                            place,
+                            participants,
+                            value: outcomes[i].value.as_ref(),
+                            best: best.as_ref(),
+                        },
+                        config,
+                    );
+                    if strategy.floored() {
+                        points.max(config.min_points)
+                    } else {
+                        points
+                    }
+                }
+                (OutcomeStatus::Dsq, _) | (_, NonFinisherScoring::Zero) => 0,
+            },
+        };
+
         // set the team's points for later usage
-        outcomes[i].team.points += current_points;
-        outcomes[i].point_value = Some(current_points);
-
-        // reduce current_points if the next result is less
-        // NOTE we have to use != here because the next value may be smaller or bigger, depending on whether value is a time or points
-        if i + 1 < outcomes.len() && outcomes[i].value != outcomes[i + 1].value {
-            current_points -= current_gap;
-            // if the gap was used, it has to be reset to one
-            current_gap = 1;
-        } else {
-            // if the current and next values are equal, the gap has to be increased by one
-            current_gap += 1;
-        }
+        outcomes[i].team.points += points;
+        outcomes[i].point_value = Some(points);
     }
 
     outcomes
 }
 
-pub async fn create_xlsx_file(pool: &PgPool, year: i32) -> ApiResult<ResultFile> {
-    // this path uses a timestamp to distinguish between versions
-    let path = format!(
-        "results-{}.xlsx",
-        humantime::format_rfc3339_seconds(SystemTime::now())
-    );
-
-    // create file
-    File::create(&path)?;
-    let workbook = Workbook::new(&path)?;
-    let (female, male) = Team::find_all_by_gender(pool, year).await?;
+/// Sort the [ParsedOutcome]s of a category with the best first and return the place of each of them.
+/// Equal values share a place, all teams that didn't finish share the place after the last finisher.
+pub fn places(outcomes: &mut [ParsedOutcome], gap: &PlaceGap) -> Vec<i32> {
+    // every kind knows whether higher or lower values are better, so the winner always ends up first
+    outcomes.sort_by(|a, b| match (&a.value, &b.value) {
+        (Some(a), Some(b)) => a.better(b),
+        // outcomes without a value come last
+        (a, b) => a.is_none().cmp(&b.is_none()),
+    });
 
-    // only write teams if any exist
-    if !female.0.is_empty() {
-        write_teams(female.0, &workbook)?;
-    }
-    if !male.0.is_empty() {
-        write_teams(male.0, &workbook)?;
+    let finishers = outcomes.iter().filter(|o| o.value.is_some()).count();
+    let mut places = Vec::<i32>::with_capacity(outcomes.len());
+    let mut place = 1;
+    for i in 0..outcomes.len() {
+        // the gap decides whether the next differing value skips all shared places
+        // NOTE we have to use != here because the next value may be smaller or bigger, depending on the direction of the game kind
+        if i > 0 && i <= finishers && outcomes[i].value != outcomes[i - 1].value {
+            place = match gap {
+                PlaceGap::Skip => i as i32 + 1,
+                PlaceGap::Dense => place + 1,
+            };
+        }
+        places.push(place);
     }
-    workbook.close()?;
 
-    // open and return file
-    Ok(ResultFile(NamedFile::open(path)?))
+    places
 }
 
-fn write_teams(mut teams: Vec<Team>, workbook: &Workbook) -> ApiResult<()> {
-    // create fonts
-    let mut heading = Format::new();
-    heading.set_bold().set_font_size(20.0);
-
-    let mut values = Format::new();
-    values.set_font_size(12.0);
-
-    // :create initial structure
-    let mut sheet = workbook.add_worksheet(Some(&teams[0].gender.to_string()))?;
-    sheet.write_string(0, 0, "Platz", Some(&heading))?;
-    sheet.write_string(0, 1, "Team", Some(&heading))?;
-    sheet.write_string(0, 2, "Punkte", Some(&heading))?;
-
-    // sort teams by points for right order in xlsx-file
-    teams.sort_by_key(|a| Reverse(a.points));
-
-    // always start at place 1
-    let mut place = 1;
+/// The current points of all teams of a year, ranked per [Category].
+#[derive(Serialize)]
+pub struct Standings {
+    pub year: i32,
+    /// Set until every game of the year has finished, later games may still change the order.
+    pub provisional: bool,
+    pub categories: Vec<CategoryStandings>,
+}
 
-    for i in 0..teams.len() {
-        let team = &teams[i];
-        let row = (i + 1) as u32;
+#[derive(Serialize)]
+pub struct CategoryStandings {
+    pub category: Category,
+    pub teams: Vec<Standing>,
+}
 
-        // write the current team to sheet
-        sheet.write_string(row, 0, &format!("{}", place), Some(&values))?;
-        sheet.write_string(row, 1, &team.name, Some(&values))?;
-        sheet.write_string(row, 2, &team.points.to_string(), Some(&values))?;
+#[derive(Serialize)]
+pub struct Standing {
+    pub place: i32,
+    pub team_id: Uuid,
+    pub trophy_id: i32,
+    pub name: String,
+    /// Already included in `points`.
+    pub adjustments: i32,
+    pub points: i32,
+    pub wins: i32,
+    pub best_place: Option<i32>,
+}
 
-        // reduce place if the next team has less points
-        if i + 1 < teams.len() && team.points > teams[i + 1].points {
-            place += 1;
-        }
-    }
+/// Rank all teams of the year by the points of the games that have been evaluated so far.
+/// Ties are broken by the [TieBreaker](crate::model::TieBreaker)s configured for the year.
+pub async fn standings(pool: &PgPool, year: i32) -> ApiResult<Standings> {
+    let mut connection = pool.acquire().await?;
+    let config = TrophyConfig::find(year, &mut connection).await?;
+    let outcomes = Outcome::find_all_for_year(year, pool).await?.0;
+    let adjustments = PointAdjustment::totals(year, &mut connection).await?;
 
-    Ok(())
-}
+    let mut categories = Vec::<CategoryStandings>::new();
+    for CategoryTeams { category, teams } in Team::find_all_by_category(pool, year).await? {
+        let teams = rank(
+            teams.0,
+            &outcomes,
+            &config.tie_breakers,
+            config.tiebreaker_game_id,
+            &config.gap,
+        )
+        .into_iter()
+        .map(
+            |Ranked {
+                 place,
+                 team,
+                 record,
+             }| Standing {
+                place,
+                team_id: team.id,
+                trophy_id: team.trophy_id,
+                name: team.name,
+                adjustments: adjustments.get(&team.id).copied().unwrap_or_default(),
+                points: team.points,
+                wins: record.wins,
+                best_place: record.best_place,
+            },
+        )
+        .collect();
 
-impl Display for ResultFile {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // this is a slight hack, but I'm okay with that
-        write!(f, "ResultFile({:#?})", self.0)
+        categories.push(CategoryStandings { category, teams });
     }
-}
 
-impl TypeInfo for ResultFile {
-    fn type_name(&self) -> String {
-        "ResultFile".to_string()
-    }
+    Ok(Standings {
+        year,
+        provisional: !is_trophy_done(&mut connection, year).await?,
+        categories,
+    })
 }
 
 #[cfg(test)]
@@ -203,7 +348,11 @@ mod tests {
     use uuid::Uuid;
 
     use super::*;
-    use crate::model::TeamGender;
+    use crate::{
+        model::{CreatePointAdjustment, MAX_POINTS, TeamGender, Value},
+        scoring::{Descending, Formula, Podium},
+        test_db::TestYear,
+    };
     use std::time::Duration;
 
     fn get_teams() -> Vec<Team> {
@@ -213,6 +362,7 @@ mod tests {
                 trophy_id: 1,
                 name: "A".to_string(),
                 gender: TeamGender::Female,
+                category_id: Uuid::nil(),
                 points: 0,
                 year: 2024,
             },
@@ -221,6 +371,7 @@ mod tests {
                 trophy_id: 2,
                 name: "B".to_string(),
                 gender: TeamGender::Female,
+                category_id: Uuid::nil(),
                 points: 0,
                 year: 2024,
             },
@@ -229,6 +380,7 @@ mod tests {
                 trophy_id: 3,
                 name: "C".to_string(),
                 gender: TeamGender::Female,
+                category_id: Uuid::nil(),
                 points: 0,
                 year: 2024,
             },
@@ -237,6 +389,7 @@ mod tests {
                 trophy_id: 4,
                 name: "D".to_string(),
                 gender: TeamGender::Female,
+                category_id: Uuid::nil(),
                 points: 0,
                 year: 2024,
             },
@@ -245,12 +398,27 @@ mod tests {
                 trophy_id: 5,
                 name: "E".to_string(),
                 gender: TeamGender::Female,
+                category_id: Uuid::nil(),
                 points: 0,
                 year: 2024,
             },
         ]
     }
 
+    fn get_many_teams(amount: i32) -> Vec<Team> {
+        (1..=amount)
+            .map(|i| Team {
+                id: Uuid::now_v7(),
+                trophy_id: i,
+                name: format!("Team {}", i),
+                gender: TeamGender::Male,
+                category_id: Uuid::nil(),
+                points: 0,
+                year: 2024,
+            })
+            .collect()
+    }
+
     fn get_outcomes(teams: Vec<Team>, values: Vec<Value>) -> Vec<ParsedOutcome> {
         assert!(teams.len() == values.len());
         let mut parsed_outcomes = Vec::<ParsedOutcome>::new();
@@ -259,8 +427,10 @@ mod tests {
             parsed_outcomes.push(ParsedOutcome {
                 game_id: Uuid::now_v7(),
                 team: team.clone(),
-                value: value.clone(),
+                status: OutcomeStatus::Ok,
+                value: Some(value.clone()),
                 point_value: None,
+                point_override: None,
             });
         }
 
@@ -282,10 +452,14 @@ mod tests {
             ],
         );
 
-        let teams: Vec<Team> = evaluate(parsed_outcomes)
-            .into_iter()
-            .map(|e| e.team)
-            .collect();
+        let teams: Vec<Team> = evaluate(
+            parsed_outcomes,
+            &Descending,
+            &TrophyConfig::default_for(2024),
+        )
+        .into_iter()
+        .map(|e| e.team)
+        .collect();
         teams.iter().for_each(|f| println!("{}", f));
 
         assert!(
@@ -330,10 +504,14 @@ mod tests {
             ],
         );
 
-        let teams: Vec<Team> = evaluate(parsed_outcomes)
-            .into_iter()
-            .map(|e| e.team)
-            .collect();
+        let teams: Vec<Team> = evaluate(
+            parsed_outcomes,
+            &Descending,
+            &TrophyConfig::default_for(2024),
+        )
+        .into_iter()
+        .map(|e| e.team)
+        .collect();
         teams.iter().for_each(|f| println!("{}", f));
 
         assert!(
@@ -379,10 +557,14 @@ mod tests {
             ],
         );
 
-        let teams: Vec<Team> = evaluate(parsed_outcomes)
-            .into_iter()
-            .map(|e| e.team)
-            .collect();
+        let teams: Vec<Team> = evaluate(
+            parsed_outcomes,
+            &Descending,
+            &TrophyConfig::default_for(2024),
+        )
+        .into_iter()
+        .map(|e| e.team)
+        .collect();
         teams.iter().for_each(|f| println!("{}", f));
 
         assert!(
@@ -427,10 +609,14 @@ mod tests {
             ],
         );
 
-        let teams: Vec<Team> = evaluate(parsed_outcomes)
-            .into_iter()
-            .map(|e| e.team)
-            .collect();
+        let teams: Vec<Team> = evaluate(
+            parsed_outcomes,
+            &Descending,
+            &TrophyConfig::default_for(2024),
+        )
+        .into_iter()
+        .map(|e| e.team)
+        .collect();
         teams.iter().for_each(|f| println!("{}", f));
 
         assert!(
@@ -460,4 +646,513 @@ mod tests {
             teams[4].points
         );
     }
+
+    /// Evaluate the values in the order of [get_teams] and return the names and points, best first.
+    fn evaluate_values(values: Vec<Value>) -> Vec<(String, i32)> {
+        evaluate(
+            get_outcomes(get_teams(), values),
+            &Descending,
+            &TrophyConfig::default_for(2024),
+        )
+        .into_iter()
+        .map(|e| (e.team.name, e.team.points))
+        .collect()
+    }
+
+    fn names_and_points(expected: &[(&str, i32)]) -> Vec<(String, i32)> {
+        expected
+            .iter()
+            .map(|(name, points)| (name.to_string(), *points))
+            .collect()
+    }
+
+    /// Checks [evaluate] with distances, where the longest wins.
+    #[test]
+    fn evaluate_distance() {
+        let actual = evaluate_values(vec![
+            Value::Distance(12_500),
+            Value::Distance(12_050),
+            Value::Distance(30_000),
+            Value::Distance(12_500),
+            Value::Distance(900),
+        ]);
+
+        assert_eq!(
+            actual,
+            names_and_points(&[("C", 50), ("A", 49), ("D", 49), ("B", 47), ("E", 46)])
+        );
+    }
+
+    /// Checks [evaluate] with lowest points, where the fewest win.
+    #[test]
+    fn evaluate_lowest_points() {
+        let actual = evaluate_values(vec![
+            Value::LowestPoints(4),
+            Value::LowestPoints(2),
+            Value::LowestPoints(7),
+            Value::LowestPoints(-1),
+            Value::LowestPoints(4),
+        ]);
+
+        assert_eq!(
+            actual,
+            names_and_points(&[("D", 50), ("B", 49), ("A", 48), ("E", 48), ("C", 46)])
+        );
+    }
+
+    /// Checks [evaluate] with hits, where the time only decides between equal hits.
+    #[test]
+    fn evaluate_hits_with_time() {
+        let hits_with_time = |hits, seconds| Value::HitsWithTime {
+            hits,
+            time: Duration::from_secs(seconds),
+        };
+        let actual = evaluate_values(vec![
+            hits_with_time(5, 60),
+            hits_with_time(7, 90),
+            hits_with_time(7, 80),
+            hits_with_time(5, 60),
+            hits_with_time(3, 10),
+        ]);
+
+        assert_eq!(
+            actual,
+            names_and_points(&[("C", 50), ("B", 49), ("A", 48), ("D", 48), ("E", 46)])
+        );
+    }
+
+    /// Outcomes of [get_teams] where B didn't start, D was disqualified and E didn't finish.
+    fn get_outcomes_with_non_finishers() -> Vec<ParsedOutcome> {
+        let mut outcomes = get_outcomes(
+            get_teams(),
+            vec![
+                Value::Seconds(Duration::from_secs(100)),
+                Value::Seconds(Duration::from_secs(1)),
+                Value::Seconds(Duration::from_secs(90)),
+                Value::Seconds(Duration::from_secs(1)),
+                Value::Seconds(Duration::from_secs(1)),
+            ],
+        );
+        for (i, status) in [
+            (1, OutcomeStatus::Dns),
+            (3, OutcomeStatus::Dsq),
+            (4, OutcomeStatus::Dnf),
+        ] {
+            outcomes[i].status = status;
+            outcomes[i].value = None;
+        }
+        outcomes
+    }
+
+    /// Checks that [evaluate] ranks teams that didn't finish last and gives them no points by default.
+    #[test]
+    fn evaluate_non_finishers_zero() {
+        let actual: Vec<(String, i32)> = evaluate(
+            get_outcomes_with_non_finishers(),
+            &Descending,
+            &TrophyConfig::default_for(2024),
+        )
+        .into_iter()
+        .map(|e| (e.team.name, e.team.points))
+        .collect();
+
+        assert_eq!(
+            actual,
+            names_and_points(&[("C", 50), ("A", 49), ("B", 0), ("D", 0), ("E", 0)])
+        );
+    }
+
+    /// Checks that [evaluate] can give teams that didn't finish the points of the place after the last finisher.
+    #[test]
+    fn evaluate_non_finishers_last() {
+        let config = TrophyConfig {
+            non_finishers: NonFinisherScoring::Last,
+            ..TrophyConfig::default_for(2024)
+        };
+        let actual: Vec<(String, i32)> =
+            evaluate(get_outcomes_with_non_finishers(), &Descending, &config)
+                .into_iter()
+                .map(|e| (e.team.name, e.team.points))
+                .collect();
+
+        // disqualified teams never receive points
+        assert_eq!(
+            actual,
+            names_and_points(&[("C", 50), ("A", 49), ("B", 48), ("D", 0), ("E", 48)])
+        );
+    }
+
+    /// Checks that [evaluate] never assigns less than the minimum, even with more teams than points.
+    #[test]
+    fn evaluate_many_teams_floor() {
+        let teams = get_many_teams(65);
+        let values = (1..=65).map(Value::Points).collect();
+        let config = TrophyConfig::default_for(2024);
+
+        let teams: Vec<Team> = evaluate(get_outcomes(teams, values), &Descending, &config)
+            .into_iter()
+            .map(|e| e.team)
+            .collect();
+
+        assert_eq!(teams[0].points, MAX_POINTS);
+        assert_eq!(teams[48].points, 2);
+        assert!(
+            teams[49..]
+                .iter()
+                .all(|team| team.points == config.min_points),
+            "All teams after place 50 receive the minimum"
+        );
+    }
+
+    /// Checks [evaluate] with a configured maximum and minimum for more than 60 teams.
+    #[test]
+    fn evaluate_many_teams_configured() {
+        let teams = get_many_teams(64);
+        let values = (1..=64).map(Value::Points).collect();
+        let config = TrophyConfig {
+            max_points: 60,
+            min_points: 5,
+            ..TrophyConfig::default_for(2024)
+        };
+
+        let teams: Vec<Team> = evaluate(get_outcomes(teams, values), &Descending, &config)
+            .into_iter()
+            .map(|e| e.team)
+            .collect();
+
+        assert_eq!(teams[0].points, 60);
+        assert_eq!(teams[55].points, 5);
+        assert!(teams.iter().all(|team| team.points >= 5));
+    }
+
+    /// Checks that ties at the end of a large field still receive the minimum.
+    #[test]
+    fn evaluate_many_teams_tied_last() {
+        let teams = get_many_teams(61);
+        let values = (1..=61).map(|i| Value::Points(i.min(10))).collect();
+        let config = TrophyConfig::default_for(2024);
+
+        let teams: Vec<Team> = evaluate(get_outcomes(teams, values), &Descending, &config)
+            .into_iter()
+            .map(|e| e.team)
+            .collect();
+
+        // 52 teams share the first place, the remaining nine follow with skipped places
+        assert!(teams[..52].iter().all(|team| team.points == MAX_POINTS));
+        assert!(
+            teams[52..]
+                .iter()
+                .all(|team| team.points == config.min_points)
+        );
+    }
+
+    /// Evaluate twelve teams with distinct points by the strategy, best first.
+    fn evaluate_twelve(strategy: &dyn ScoringStrategy, config: &TrophyConfig) -> Vec<i32> {
+        let values = (1..=12).map(Value::Points).collect();
+        evaluate(get_outcomes(get_many_teams(12), values), strategy, config)
+            .into_iter()
+            .map(|e| e.team.points)
+            .collect()
+    }
+
+    /// Checks that [Podium] isn't raised to the minimum, so only the first three places score.
+    #[test]
+    fn evaluate_podium_ignores_min_points() {
+        let config = TrophyConfig {
+            min_points: 5,
+            ..TrophyConfig::default_for(2024)
+        };
+
+        assert_eq!(
+            evaluate_twelve(&Podium, &config),
+            vec![3, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    /// Checks that [Formula] isn't raised to the minimum, so only the first ten places score.
+    #[test]
+    fn evaluate_formula_ignores_min_points() {
+        let config = TrophyConfig {
+            min_points: 5,
+            ..TrophyConfig::default_for(2024)
+        };
+
+        assert_eq!(
+            evaluate_twelve(&Formula, &config),
+            vec![25, 18, 15, 12, 10, 8, 6, 4, 2, 1, 0, 0]
+        );
+    }
+
+    /// Checks that a point value set by hand survives the evaluation, while everyone else is scored as usual.
+    #[test]
+    fn evaluate_keeps_point_overrides() {
+        let values = (1..=12).map(Value::Points).collect();
+        let mut outcomes = get_outcomes(get_many_teams(12), values);
+        // the worst team has won a protest
+        outcomes[0].point_override = Some(7);
+
+        let points: Vec<i32> = evaluate(outcomes, &Podium, &TrophyConfig::default_for(2024))
+            .into_iter()
+            .map(|e| e.team.points)
+            .collect();
+        assert_eq!(points, vec![3, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 7]);
+    }
+
+    /// Checks [evaluate] with a dense gap, which doesn't skip places after ties.
+    #[test]
+    fn evaluate_points_dense_gap() {
+        let teams = get_teams();
+        let parsed_outcomes = get_outcomes(
+            teams,
+            vec![
+                Value::Points(100),
+                Value::Points(100),
+                Value::Points(100),
+                Value::Points(5),
+                Value::Points(1000),
+            ],
+        );
+        let config = TrophyConfig {
+            gap: PlaceGap::Dense,
+            ..TrophyConfig::default_for(2024)
+        };
+
+        let points: Vec<i32> = evaluate(parsed_outcomes, &Descending, &config)
+            .into_iter()
+            .map(|e| e.team.points)
+            .collect();
+
+        assert_eq!(
+            points,
+            vec![
+                MAX_POINTS,
+                MAX_POINTS - 1,
+                MAX_POINTS - 1,
+                MAX_POINTS - 1,
+                MAX_POINTS - 2
+            ]
+        );
+    }
+
+    /// Needs a database with the current schema at `DATABASE_URL`, run with `cargo test -- --ignored`.
+    #[actix_web::test]
+    #[ignore = "requires a database at DATABASE_URL"]
+    async fn evaluate_trophy_rolls_back_on_parse_error() {
+        let test_year = TestYear::new().await;
+        let (pool, year) = (&test_year.pool, test_year.year);
+
+        // games are evaluated in order of creation, so the first one is written before the second one fails
+        let mut games = Vec::<Game>::new();
+        for (trophy_id, name) in [(1, "First"), (2, "Second")] {
+            games.push(test_year.game(test_year.create_game(trophy_id, name)).await);
+        }
+        for trophy_id in [1, 2] {
+            test_year.team(trophy_id, TeamGender::Female).await;
+        }
+
+        sqlx::query("UPDATE game_team SET data = '10' FROM games WHERE game_team.game_id = games.id AND games.year = $1")
+            .bind(year)
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE game_team SET data = 'not a number' WHERE game_id = $1")
+            .bind(games[1].id)
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE games SET closed = true WHERE year = $1")
+            .bind(year)
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE teams SET points = 7 WHERE year = $1")
+            .bind(year)
+            .execute(pool)
+            .await
+            .unwrap();
+
+        let result = evaluate_trophy(pool, year).await;
+
+        let points: Vec<i32> = sqlx::query_scalar("SELECT points FROM teams WHERE year = $1")
+            .bind(year)
+            .fetch_all(pool)
+            .await
+            .unwrap();
+        let point_values: Vec<Option<i32>> = sqlx::query_scalar(
+            "SELECT point_value FROM game_team INNER JOIN games ON game_team.game_id = games.id WHERE games.year = $1",
+        )
+        .bind(year)
+        .fetch_all(pool)
+        .await
+        .unwrap();
+
+        test_year.clean_up().await;
+
+        assert!(result.is_err());
+        assert_eq!(points, vec![7, 7]);
+        assert_eq!(point_values, vec![None; 4]);
+    }
+
+    /// Two closed games of points with three teams in the same category, every team wins one game.
+    async fn played_year() -> (TestYear, Vec<Game>, Vec<Team>) {
+        let test_year = TestYear::new().await;
+        let mut games = Vec::<Game>::new();
+        for (trophy_id, name) in [(1, "Kegeln"), (2, "Werfen")] {
+            games.push(test_year.game(test_year.create_game(trophy_id, name)).await);
+        }
+        let mut teams = Vec::<Team>::new();
+        for trophy_id in 1..=3 {
+            teams.push(test_year.team(trophy_id, TeamGender::Female).await);
+        }
+        for (game, data) in games.iter().zip([["10", "20", "30"], ["30", "20", "10"]]) {
+            for (team, data) in teams.iter().zip(data) {
+                test_year.set_data(game, team, data).await;
+            }
+        }
+        test_year.close_games().await;
+
+        (test_year, games, teams)
+    }
+
+    /// Needs a database with the current schema at `DATABASE_URL`, run with `cargo test -- --ignored`.
+    #[actix_web::test]
+    #[ignore = "requires a database at DATABASE_URL"]
+    async fn recompute_can_be_repeated() {
+        let (test_year, games, teams) = played_year().await;
+        let (pool, year) = (&test_year.pool, test_year.year);
+
+        evaluate_trophy(pool, year).await.unwrap();
+        let evaluated = test_year.points().await;
+        // correct an outcome after the evaluation
+        test_year.set_data(&games[0], &teams[0], "40").await;
+        let changes = recompute_trophy(pool, year).await.unwrap().0;
+        let recomputed = test_year.points().await;
+        let repeated_changes = recompute_trophy(pool, year).await.unwrap().0;
+        let repeated = test_year.points().await;
+
+        test_year.clean_up().await;
+
+        assert_eq!(evaluated, vec![98, 98, 98]);
+        assert_eq!(recomputed, vec![100, 97, 97]);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(repeated, recomputed);
+        assert!(repeated_changes.is_empty());
+    }
+
+    /// The stored point values of the game, ordered by the trophy-id of their teams.
+    async fn point_values(pool: &PgPool, game: &Game) -> Vec<Option<i32>> {
+        sqlx::query_scalar(
+            "SELECT point_value FROM game_team INNER JOIN teams ON game_team.team_id = teams.id WHERE game_id = $1 ORDER BY teams.trophy_id",
+        )
+        .bind(game.id)
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    /// Needs a database with the current schema at `DATABASE_URL`, run with `cargo test -- --ignored`.
+    #[actix_web::test]
+    #[ignore = "requires a database at DATABASE_URL"]
+    async fn evaluating_a_game_leaves_the_others_alone() {
+        let (test_year, games, _) = played_year().await;
+        let pool = &test_year.pool;
+
+        let evaluated = evaluate_single_game(games[0].id, pool).await.unwrap().0;
+        let first = point_values(pool, &games[0]).await;
+        let untouched = point_values(pool, &games[1]).await;
+        let after_first = test_year.points().await;
+        evaluate_single_game(games[1].id, pool).await.unwrap();
+        let first_again = point_values(pool, &games[0]).await;
+        let after_both = test_year.points().await;
+
+        test_year.clean_up().await;
+
+        assert_eq!(evaluated.len(), 3);
+        assert_eq!(first, vec![Some(48), Some(49), Some(50)]);
+        assert_eq!(untouched, vec![None; 3]);
+        assert_eq!(after_first, vec![48, 49, 50]);
+        assert_eq!(first_again, first);
+        assert_eq!(after_both, vec![98, 98, 98]);
+    }
+
+    /// Needs a database with the current schema at `DATABASE_URL`, run with `cargo test -- --ignored`.
+    #[actix_web::test]
+    #[ignore = "requires a database at DATABASE_URL"]
+    async fn preview_matches_the_evaluation_without_writing() {
+        let (test_year, games, _) = played_year().await;
+        let (pool, year) = (&test_year.pool, test_year.year);
+
+        let preview = preview_trophy(pool, year).await.unwrap();
+        let stored_before = (
+            test_year.points().await,
+            point_values(pool, &games[0]).await,
+        );
+        evaluate_trophy(pool, year).await.unwrap();
+        let mut connection = pool.acquire().await.unwrap();
+        let mut evaluated = Vec::new();
+        for outcome in &preview.outcomes {
+            let stored = Outcome::find(outcome.game_id, outcome.team_id, &mut connection)
+                .await
+                .unwrap();
+            evaluated.push(stored.point_value);
+        }
+        let totals = test_year.points().await;
+
+        drop(connection);
+        test_year.clean_up().await;
+
+        assert_eq!(stored_before, (vec![0, 0, 0], vec![None; 3]));
+        assert_eq!(preview.outcomes.len(), 6);
+        let previewed: Vec<Option<i32>> = preview
+            .outcomes
+            .iter()
+            .map(|outcome| Some(outcome.point_value))
+            .collect();
+        assert_eq!(previewed, evaluated);
+        let mut previewed_totals: Vec<(i32, i32)> = preview
+            .teams
+            .iter()
+            .map(|team| (team.trophy_id, team.points))
+            .collect();
+        previewed_totals.sort();
+        assert_eq!(previewed_totals, vec![(1, 98), (2, 98), (3, 98)]);
+        assert_eq!(totals, vec![98, 98, 98]);
+    }
+
+    /// Needs a database with the current schema at `DATABASE_URL`, run with `cargo test -- --ignored`.
+    #[actix_web::test]
+    #[ignore = "requires a database at DATABASE_URL"]
+    async fn adjustments_count_in_the_standings() {
+        let (test_year, _, teams) = played_year().await;
+        let (pool, year) = (&test_year.pool, test_year.year);
+
+        evaluate_trophy(pool, year).await.unwrap();
+        let bonus = CreatePointAdjustment {
+            team_id: teams[2].id,
+            game_id: None,
+            amount: 5,
+            reason: "Fair play".to_string(),
+        };
+        PointAdjustment::create(bonus, pool).await.unwrap();
+        let standings = standings(pool, year).await.unwrap();
+
+        test_year.clean_up().await;
+
+        let ranked: Vec<(i32, i32, i32, i32)> = standings.categories[0]
+            .teams
+            .iter()
+            .map(|standing| {
+                (
+                    standing.place,
+                    standing.trophy_id,
+                    standing.adjustments,
+                    standing.points,
+                )
+            })
+            .collect();
+        // the bonus breaks the tie of the three teams
+        assert_eq!(ranked[0], (1, 3, 5, 103));
+        assert_eq!(ranked[1].2, 0);
+        assert_eq!(ranked[1].3, 98);
+    }
 }
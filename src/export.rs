@@ -0,0 +1,883 @@
+use crate::{
+    ApiResult, TypeInfo,
+    eval::{CategoryStandings, Standings, places, standings},
+    model::{
+        CustomError, Game, Lang, Outcome, OutcomeStatus, ParsedOutcome, Team, TrophyConfig, Value,
+    },
+    pdf::{Document, Font, MARGIN, PAGE_HEIGHT, PAGE_WIDTH},
+};
+use actix_web::{
+    HttpRequest, HttpResponse, Responder,
+    body::BoxBody,
+    http::header::{ContentDisposition, DispositionParam, DispositionType},
+};
+use serde::Serialize;
+use sqlx::PgPool;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    fs,
+    time::Duration,
+};
+use uuid::Uuid;
+use xlsxwriter::*;
+
+/// An exported file, which is sent as download and never touches the working directory.
+pub struct ResultFile {
+    pub name: String,
+    pub content_type: &'static str,
+    pub bytes: Vec<u8>,
+}
+
+/// The x-positions of place, trophy id, team and points in the PDF results list.
+const RESULT_COLUMNS: [f32; 4] = [
+    MARGIN,
+    MARGIN + 50.0,
+    MARGIN + 100.0,
+    PAGE_WIDTH - MARGIN - 60.0,
+];
+
+/// Leaves room for the headings at the top of each page.
+const RESULTS_PER_PAGE: usize = 32;
+
+/// The ranking of the year as it's exported to every format, so place numbers are always the same.
+#[derive(Serialize)]
+pub struct Results {
+    pub year: i32,
+    /// Set until every game of the year has finished, see [Standings].
+    pub provisional: bool,
+    /// The names of all games of the year, in the order of [ResultRow::games].
+    pub games: Vec<String>,
+    /// Grouped by category and ordered by place within each category.
+    pub teams: Vec<ResultRow>,
+}
+
+#[derive(Serialize)]
+pub struct ResultRow {
+    pub place: i32,
+    pub team_id: Uuid,
+    pub trophy_id: i32,
+    pub team: String,
+    pub category: String,
+    /// Already included in `total`.
+    pub adjustments: i32,
+    pub total: i32,
+    pub games: Vec<GameResult>,
+}
+
+#[derive(Serialize)]
+pub struct GameResult {
+    pub game_id: Uuid,
+    pub game: String,
+    /// Not set while the game hasn't been evaluated yet.
+    pub point_value: Option<i32>,
+}
+
+/// A single team in the sheet of a [Game].
+struct GameRow {
+    category: String,
+    /// Not set while the team hasn't played yet.
+    place: Option<i32>,
+    team: String,
+    /// The stored data or the status of teams that didn't finish.
+    value: String,
+    penalty: Option<String>,
+    point_value: Option<i32>,
+}
+
+/// The [Results] of the year as sent as JSON, with the names of the categories in the requested language.
+pub async fn find_results(pool: &PgPool, year: i32, lang: Lang) -> ApiResult<Results> {
+    let mut results = results_of_year(pool, year).await?;
+    for team in &mut results.teams {
+        team.category = lang.category(&team.category);
+    }
+
+    Ok(results)
+}
+
+/// Collect the [Results] of the year from the standings and the stored outcomes.
+async fn results_of_year(pool: &PgPool, year: i32) -> ApiResult<Results> {
+    let standings = standings(pool, year).await?;
+    let games = Game::find_all(&mut *pool.acquire().await?, year).await?.0;
+    let outcomes = Outcome::find_all_for_year(year, pool).await?.0;
+
+    Ok(results(standings, &games, &outcomes))
+}
+
+fn results(standings: Standings, games: &[Game], outcomes: &[Outcome]) -> Results {
+    let point_values: HashMap<(Uuid, Uuid), i32> = outcomes
+        .iter()
+        .filter_map(|o| Some(((o.game_id, o.team_id), o.point_value?)))
+        .collect();
+    let point_values = &point_values;
+
+    let teams = standings
+        .categories
+        .into_iter()
+        .flat_map(|CategoryStandings { category, teams }| {
+            teams.into_iter().map(move |team| ResultRow {
+                place: team.place,
+                team_id: team.team_id,
+                trophy_id: team.trophy_id,
+                team: team.name,
+                category: category.name.clone(),
+                adjustments: team.adjustments,
+                total: team.points,
+                games: games
+                    .iter()
+                    .map(|game| GameResult {
+                        game_id: game.id,
+                        game: game.name.clone(),
+                        point_value: point_values.get(&(game.id, team.team_id)).copied(),
+                    })
+                    .collect(),
+            })
+        })
+        .collect();
+
+    Results {
+        year: standings.year,
+        provisional: standings.provisional,
+        games: games.iter().map(|game| game.name.clone()).collect(),
+        teams,
+    }
+}
+
+/// Create the workbook of the year from the stored outcomes:
+/// one sheet per category with the standings, a matrix of all teams and games and one sheet per game.
+pub async fn create_xlsx_file(pool: &PgPool, year: i32, lang: Lang) -> ApiResult<ResultFile> {
+    let standings = standings(pool, year).await?;
+    let mut connection = pool.acquire().await?;
+    let config = TrophyConfig::find(year, &mut connection).await?;
+    let games = Game::find_all(&mut connection, year).await?.0;
+    let outcomes = Outcome::find_all_for_year(year, pool).await?.0;
+    let teams: HashMap<Uuid, Team> = Team::find_all(pool, year)
+        .await?
+        .0
+        .into_iter()
+        .map(|team| (team.id, team))
+        .collect();
+    let results = results(standings, &games, &outcomes);
+
+    // xlsxwriter can only write to a path, the temporary file is removed as soon as it's dropped
+    let file = tempfile::Builder::new()
+        .prefix("results-")
+        .suffix(".xlsx")
+        .tempfile()?;
+    let path = file.path().to_str().ok_or(CustomError::XlsxError {
+        message: format!("Invalid path for the workbook: {:?}", file.path()),
+    })?;
+    let workbook = Workbook::new(path)?;
+    let mut names = SheetNames::default();
+    // every category gets a sheet of its own - categories without teams are skipped
+    for category in results.categories() {
+        write_teams(category, lang, &workbook, &mut names)?;
+    }
+    write_matrix(&results, lang, &workbook, &mut names)?;
+    for game in &games {
+        let rows = game_rows(game, &outcomes, &teams, &results, &config, lang);
+        write_game(game, &rows, lang, &workbook, &mut names)?;
+    }
+    workbook.close()?;
+
+    Ok(ResultFile {
+        name: format!("results-{}.xlsx", year),
+        content_type: "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        bytes: fs::read(file.path())?,
+    })
+}
+
+/// Create a CSV-file with one line per team, the columns match the overview of the workbook.
+pub async fn create_csv_file(pool: &PgPool, year: i32, lang: Lang) -> ApiResult<ResultFile> {
+    let results = results_of_year(pool, year).await?;
+
+    Ok(ResultFile {
+        name: format!("results-{}.csv", year),
+        content_type: "text/csv; charset=utf-8",
+        bytes: results.to_csv(lang).into_bytes(),
+    })
+}
+
+impl Results {
+    /// The teams of each category, in the order of the standings.
+    fn categories(&self) -> impl Iterator<Item = &[ResultRow]> {
+        self.teams.chunk_by(|a, b| a.category == b.category)
+    }
+
+    /// Fails for years without teams, so nobody ends up printing an empty PDF.
+    fn ensure_teams(&self) -> ApiResult<()> {
+        if self.teams.is_empty() {
+            return Err(CustomError::NotFoundError {
+                message: format!("There are no teams in {}.", self.year),
+            });
+        }
+        Ok(())
+    }
+
+    /// The teams to print certificates for, only the specified one if set.
+    /// Fails while the places are provisional, certificates must only show final places.
+    fn certificate_teams(&self, team_id: Option<Uuid>) -> ApiResult<Vec<&ResultRow>> {
+        self.ensure_teams()?;
+        if self.provisional {
+            return Err(CustomError::EarlyEvaluationError {
+                message: format!(
+                    "The places of {} are provisional until every game has finished.",
+                    self.year
+                ),
+            });
+        }
+        Ok(self
+            .teams
+            .iter()
+            .filter(|team| team_id.is_none_or(|id| team.team_id == id))
+            .collect())
+    }
+
+    fn to_csv(&self, lang: Lang) -> String {
+        let labels = lang.labels();
+        let mut header = vec![labels.place, labels.trophy_id, labels.team, labels.category];
+        header.extend(self.games.iter().map(String::as_str));
+        header.extend([labels.adjustments, labels.points]);
+
+        let mut csv = csv_line(header);
+        for row in &self.teams {
+            let mut fields = vec![
+                row.place.to_string(),
+                row.trophy_id.to_string(),
+                row.team.clone(),
+                lang.category(&row.category),
+            ];
+            // games that haven't been evaluated yet stay empty
+            fields.extend(
+                row.games
+                    .iter()
+                    .map(|game| game.point_value.map(|p| p.to_string()).unwrap_or_default()),
+            );
+            fields.extend([row.adjustments.to_string(), row.total.to_string()]);
+            csv.push_str(&csv_line(fields));
+        }
+
+        csv
+    }
+}
+
+/// Create a PDF listing the ranking of each category, e.g. for the notice board.
+/// Every category starts on a new page.
+pub async fn create_results_pdf(pool: &PgPool, year: i32, lang: Lang) -> ApiResult<ResultFile> {
+    let results = results_of_year(pool, year).await?;
+    results.ensure_teams()?;
+    let labels = lang.labels();
+    let title = format!("{} {}", labels.results, year);
+
+    let mut document = Document::new(&title);
+    for teams in results.categories() {
+        let mut category = lang.category(&teams[0].category);
+        if results.provisional {
+            category.push_str(&format!(" ({})", labels.provisional));
+        }
+
+        for page_teams in teams.chunks(RESULTS_PER_PAGE) {
+            let page = document.add_page();
+            let mut y = PAGE_HEIGHT - MARGIN - 20.0;
+            page.text(MARGIN, y, Font::Bold, 20.0, &title);
+            y -= 24.0;
+            page.text(MARGIN, y, Font::Regular, 14.0, &category);
+            y -= 36.0;
+            for (x, heading) in RESULT_COLUMNS.iter().zip([
+                labels.place,
+                labels.trophy_id,
+                labels.team,
+                labels.points,
+            ]) {
+                page.text(*x, y, Font::Bold, 12.0, heading);
+            }
+            page.rule(y - 6.0);
+
+            for team in page_teams {
+                y -= 20.0;
+                let values = [
+                    team.place.to_string(),
+                    team.trophy_id.to_string(),
+                    team.team.clone(),
+                    team.total.to_string(),
+                ];
+                for (x, value) in RESULT_COLUMNS.iter().zip(values) {
+                    page.text(*x, y, Font::Regular, 12.0, &value);
+                }
+            }
+        }
+    }
+
+    Ok(ResultFile {
+        name: format!("results-{}.pdf", year),
+        content_type: "application/pdf",
+        bytes: document.finish(),
+    })
+}
+
+/// Create a PDF with a certificate per team from the template of the year, or only for the specified team.
+/// Certificates can only be printed once every game has finished.
+pub async fn create_certificates_pdf(
+    pool: &PgPool,
+    year: i32,
+    team_id: Option<Uuid>,
+    lang: Lang,
+) -> ApiResult<ResultFile> {
+    let results = results_of_year(pool, year).await?;
+    let teams = results.certificate_teams(team_id)?;
+    let config = TrophyConfig::find(year, &mut *pool.acquire().await?).await?;
+
+    let mut document = Document::new(&format!("{} {}", lang.labels().certificates, year));
+    for team in &teams {
+        let page = document.add_page();
+        let lines = certificate_lines(config.certificate_template(lang), team, year, lang);
+        let mut lines = lines.iter();
+        if let Some(heading) = lines.next() {
+            page.text_centered(PAGE_HEIGHT * 0.7, 40.0, heading);
+        }
+        for (i, line) in lines.enumerate() {
+            page.text_centered(PAGE_HEIGHT * 0.6 - 34.0 * i as f32, 20.0, line);
+        }
+    }
+
+    let name = match teams.as_slice() {
+        [team] if team_id.is_some() => format!("certificate-{}-{}.pdf", year, team.trophy_id),
+        _ => format!("certificates-{}.pdf", year),
+    };
+    Ok(ResultFile {
+        name,
+        content_type: "application/pdf",
+        bytes: document.finish(),
+    })
+}
+
+/// Fill in the placeholders of the template, every line of the template is a line of the certificate.
+fn certificate_lines(template: &str, team: &ResultRow, year: i32, lang: Lang) -> Vec<String> {
+    template
+        .lines()
+        .map(|line| {
+            line.replace("{team}", &team.team)
+                .replace("{category}", &lang.category(&team.category))
+                .replace("{place}", &team.place.to_string())
+                .replace("{points}", &team.total.to_string())
+                .replace("{year}", &year.to_string())
+        })
+        .collect()
+}
+
+/// Join the fields to a line as described in RFC 4180, quoting fields only if necessary.
+fn csv_line<S: AsRef<str>>(fields: impl IntoIterator<Item = S>) -> String {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|field| {
+            let field = field.as_ref();
+            if field.contains([',', '"', '\r', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect();
+
+    format!("{}\r\n", fields.join(","))
+}
+
+fn formats() -> (Format, Format) {
+    let mut heading = Format::new();
+    heading.set_bold().set_font_size(20.0);
+
+    let mut values = Format::new();
+    values.set_font_size(12.0);
+
+    (heading, values)
+}
+
+fn write_teams(
+    teams: &[ResultRow],
+    lang: Lang,
+    workbook: &Workbook,
+    names: &mut SheetNames,
+) -> ApiResult<()> {
+    let (heading, values) = formats();
+    let labels = lang.labels();
+
+    // :create initial structure
+    let mut sheet =
+        workbook.add_worksheet(Some(&names.unique(&lang.category(&teams[0].category))))?;
+    sheet.write_string(0, 0, labels.place, Some(&heading))?;
+    sheet.write_string(0, 1, labels.team, Some(&heading))?;
+    sheet.write_string(0, 2, labels.adjustments, Some(&heading))?;
+    sheet.write_string(0, 3, labels.points, Some(&heading))?;
+
+    // results are already in the right order for the xlsx-file
+    for (i, team) in teams.iter().enumerate() {
+        let row = (i + 1) as u32;
+
+        // write the current team to sheet
+        sheet.write_string(row, 0, &team.place.to_string(), Some(&values))?;
+        sheet.write_string(row, 1, &team.team, Some(&values))?;
+        sheet.write_string(row, 2, &team.adjustments.to_string(), Some(&values))?;
+        sheet.write_string(row, 3, &team.total.to_string(), Some(&values))?;
+    }
+
+    Ok(())
+}
+
+/// Write the point values of all teams (rows) in all games (columns), ordered like the standings.
+fn write_matrix(
+    results: &Results,
+    lang: Lang,
+    workbook: &Workbook,
+    names: &mut SheetNames,
+) -> ApiResult<()> {
+    let (heading, values) = formats();
+    let labels = lang.labels();
+
+    let mut sheet = workbook.add_worksheet(Some(&names.unique(labels.overview)))?;
+    sheet.write_string(0, 0, labels.category, Some(&heading))?;
+    sheet.write_string(0, 1, labels.place, Some(&heading))?;
+    sheet.write_string(0, 2, labels.team, Some(&heading))?;
+    for (i, game) in results.games.iter().enumerate() {
+        sheet.write_string(0, 3 + i as u16, game, Some(&heading))?;
+    }
+    let adjustments_column = 3 + results.games.len() as u16;
+    sheet.write_string(0, adjustments_column, labels.adjustments, Some(&heading))?;
+    sheet.write_string(0, adjustments_column + 1, labels.points, Some(&heading))?;
+
+    for (i, team) in results.teams.iter().enumerate() {
+        let row = (i + 1) as u32;
+
+        sheet.write_string(row, 0, &lang.category(&team.category), Some(&values))?;
+        sheet.write_string(row, 1, &team.place.to_string(), Some(&values))?;
+        sheet.write_string(row, 2, &team.team, Some(&values))?;
+        for (j, game) in team.games.iter().enumerate() {
+            // games that haven't been evaluated yet stay empty
+            if let Some(point_value) = game.point_value {
+                sheet.write_string(row, 3 + j as u16, &point_value.to_string(), Some(&values))?;
+            }
+        }
+        sheet.write_string(
+            row,
+            adjustments_column,
+            &team.adjustments.to_string(),
+            Some(&values),
+        )?;
+        sheet.write_string(
+            row,
+            adjustments_column + 1,
+            &team.total.to_string(),
+            Some(&values),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn write_game(
+    game: &Game,
+    rows: &[GameRow],
+    lang: Lang,
+    workbook: &Workbook,
+    names: &mut SheetNames,
+) -> ApiResult<()> {
+    let (heading, values) = formats();
+    let labels = lang.labels();
+
+    let mut sheet = workbook.add_worksheet(Some(&names.unique(&game.name)))?;
+    sheet.write_string(0, 0, labels.category, Some(&heading))?;
+    sheet.write_string(0, 1, labels.place, Some(&heading))?;
+    sheet.write_string(0, 2, labels.team, Some(&heading))?;
+    // the kind tells how to read the values, e.g. seconds or metres
+    let value = format!("{} ({})", labels.value, lang.game_kind(&game.kind));
+    sheet.write_string(0, 3, &value, Some(&heading))?;
+    sheet.write_string(0, 4, labels.penalty, Some(&heading))?;
+    sheet.write_string(0, 5, labels.points, Some(&heading))?;
+
+    for (i, game_row) in rows.iter().enumerate() {
+        let row = (i + 1) as u32;
+
+        sheet.write_string(row, 0, &game_row.category, Some(&values))?;
+        if let Some(place) = game_row.place {
+            sheet.write_string(row, 1, &place.to_string(), Some(&values))?;
+        }
+        sheet.write_string(row, 2, &game_row.team, Some(&values))?;
+        sheet.write_string(row, 3, &game_row.value, Some(&values))?;
+        if let Some(penalty) = &game_row.penalty {
+            sheet.write_string(row, 4, penalty, Some(&values))?;
+        }
+        if let Some(point_value) = game_row.point_value {
+            sheet.write_string(row, 5, &point_value.to_string(), Some(&values))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Rank the outcomes of the [Game] per category just like the evaluation does, teams that haven't played yet come last.
+/// Outcomes whose data can't be parsed (anymore) come last as well, so a single one doesn't fail the whole export.
+fn game_rows(
+    game: &Game,
+    outcomes: &[Outcome],
+    teams: &HashMap<Uuid, Team>,
+    results: &Results,
+    config: &TrophyConfig,
+    lang: Lang,
+) -> Vec<GameRow> {
+    let outcomes: HashMap<Uuid, &Outcome> = outcomes
+        .iter()
+        .filter(|o| o.game_id == game.id)
+        .map(|o| (o.team_id, o))
+        .collect();
+
+    let mut rows = Vec::<GameRow>::new();
+    for category in results.categories() {
+        let mut parsed = Vec::<ParsedOutcome>::new();
+        let mut unranked = Vec::<&Outcome>::new();
+        for standing in category {
+            let (Some(outcome), Some(team)) = (
+                outcomes.get(&standing.team_id),
+                teams.get(&standing.team_id),
+            ) else {
+                continue;
+            };
+
+            if outcome.status == OutcomeStatus::Ok && outcome.data.is_none() {
+                unranked.push(outcome);
+                continue;
+            }
+            match ParsedOutcome::from_outcome(outcome, &game.kind, team.clone()) {
+                Ok(parsed_outcome) => parsed.push(parsed_outcome),
+                Err(_) => unranked.push(outcome),
+            }
+        }
+
+        let places = places(&mut parsed, &config.gap);
+        let ranked = parsed
+            .iter()
+            .zip(places)
+            .map(|(parsed, place)| (outcomes[&parsed.team.id], Some(place)));
+        let unranked = unranked.into_iter().map(|outcome| (outcome, None));
+        for (outcome, place) in ranked.chain(unranked) {
+            rows.push(GameRow {
+                category: lang.category(&category[0].category),
+                place,
+                team: outcome.team_name.clone(),
+                value: match outcome.status {
+                    OutcomeStatus::Ok => outcome.data.clone().unwrap_or_default(),
+                    status => lang.status(status).to_string(),
+                },
+                penalty: penalty(outcome),
+                point_value: outcome.point_value,
+            });
+        }
+    }
+
+    rows
+}
+
+fn penalty(outcome: &Outcome) -> Option<String> {
+    match (outcome.penalty_points, outcome.penalty_millis) {
+        (Some(points), _) => Some(points.to_string()),
+        // time-penalties look just like the data of time-games
+        (None, Some(millis)) => Some(format!(
+            "+{}",
+            Value::Seconds(Duration::from_millis(millis as u64))
+        )),
+        (None, None) => None,
+    }
+}
+
+/// Excel requires sheet names to be unique (ignoring case), at most 31 characters long and free of `[]:*?/\`.
+#[derive(Default)]
+struct SheetNames(HashSet<String>);
+
+impl SheetNames {
+    fn unique(&mut self, name: &str) -> String {
+        let name: String = name
+            .chars()
+            .map(|c| if "[]:*?/\\".contains(c) { '-' } else { c })
+            .take(31)
+            .collect();
+
+        let mut unique = name.clone();
+        let mut i = 2;
+        while !self.0.insert(unique.to_lowercase()) {
+            let suffix = format!(" ({})", i);
+            unique = name
+                .chars()
+                .take(31 - suffix.chars().count())
+                .chain(suffix.chars())
+                .collect();
+            i += 1;
+        }
+
+        unique
+    }
+}
+
+impl Responder for ResultFile {
+    type Body = BoxBody;
+
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse<Self::Body> {
+        HttpResponse::Ok()
+            .content_type(self.content_type)
+            .insert_header(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(self.name)],
+            })
+            .body(self.bytes)
+    }
+}
+
+impl Display for ResultFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ResultFile(name: {}, content_type: {}, bytes: {})",
+            self.name,
+            self.content_type,
+            self.bytes.len()
+        )
+    }
+}
+
+impl TypeInfo for ResultFile {
+    fn type_name(&self) -> String {
+        "ResultFile".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{AttemptAggregation, GameKind, ScoringKind, TeamGender};
+
+    #[test]
+    fn csv_fields_are_quoted_if_necessary() {
+        assert_eq!(csv_line(["1", "Die Füchse"]), "1,Die Füchse\r\n");
+        assert_eq!(
+            csv_line(["Rot, Weiß", "Die \"Adler\"", "a\nb"]),
+            "\"Rot, Weiß\",\"Die \"\"Adler\"\"\",\"a\nb\"\r\n"
+        );
+    }
+
+    #[test]
+    fn csv_has_a_line_per_team() {
+        let game = |name: &str, point_value| GameResult {
+            game_id: Uuid::nil(),
+            game: name.to_string(),
+            point_value,
+        };
+        let results = Results {
+            year: 2024,
+            provisional: true,
+            games: vec!["Kegeln".to_string(), "Lauf".to_string()],
+            teams: vec![
+                ResultRow {
+                    place: 1,
+                    team_id: Uuid::nil(),
+                    trophy_id: 3,
+                    team: "Team, 3".to_string(),
+                    category: "Female".to_string(),
+                    adjustments: -2,
+                    total: 48,
+                    games: vec![game("Kegeln", Some(50)), game("Lauf", None)],
+                },
+                ResultRow {
+                    place: 1,
+                    team_id: Uuid::nil(),
+                    trophy_id: 7,
+                    team: "Team 7".to_string(),
+                    category: "Male".to_string(),
+                    adjustments: 0,
+                    total: 50,
+                    games: vec![game("Kegeln", Some(50)), game("Lauf", None)],
+                },
+            ],
+        };
+
+        assert_eq!(
+            results.to_csv(Lang::De),
+            "Platz,Nr.,Team,Kategorie,Kegeln,Lauf,Anpassungen,Punkte\r\n\
+             1,3,\"Team, 3\",Damen,50,,-2,48\r\n\
+             1,7,Team 7,Herren,50,,0,50\r\n"
+        );
+        assert!(results.to_csv(Lang::En).starts_with(
+            "Place,No.,Team,Category,Kegeln,Lauf,Adjustments,Points\r\n1,3,\"Team, 3\",Women,"
+        ));
+        assert_eq!(results.categories().count(), 2);
+    }
+
+    #[test]
+    fn certificates_need_final_places() {
+        let team = |team_id| ResultRow {
+            place: 1,
+            team_id,
+            trophy_id: 1,
+            team: "Team 1".to_string(),
+            category: "Female".to_string(),
+            adjustments: 0,
+            total: 50,
+            games: Vec::new(),
+        };
+        let results = |provisional, teams| Results {
+            year: 2024,
+            provisional,
+            games: Vec::new(),
+            teams,
+        };
+        let id = Uuid::now_v7();
+
+        assert!(matches!(
+            results(false, Vec::new()).certificate_teams(None),
+            Err(CustomError::NotFoundError { .. })
+        ));
+        assert!(matches!(
+            results(true, vec![team(id)]).certificate_teams(None),
+            Err(CustomError::EarlyEvaluationError { .. })
+        ));
+        let results = results(false, vec![team(id), team(Uuid::nil())]);
+        assert_eq!(results.certificate_teams(None).unwrap().len(), 2);
+        assert_eq!(results.certificate_teams(Some(id)).unwrap()[0].team_id, id);
+    }
+
+    #[test]
+    fn certificates_fill_in_the_placeholders() {
+        let team = ResultRow {
+            place: 2,
+            team_id: Uuid::nil(),
+            trophy_id: 7,
+            team: "Die Füchse".to_string(),
+            category: "Female".to_string(),
+            adjustments: 0,
+            total: 98,
+            games: Vec::new(),
+        };
+
+        assert_eq!(
+            certificate_lines(
+                Lang::De.labels().certificate_template,
+                &team,
+                2024,
+                Lang::De
+            ),
+            [
+                "Urkunde",
+                "Die Füchse",
+                "belegt in der Kategorie Damen",
+                "mit 98 Punkten den",
+                "2. Platz",
+                "bei der Klostertrophy 2024",
+            ]
+        );
+    }
+
+    #[test]
+    fn sheet_names_are_valid_and_unique() {
+        let mut names = SheetNames::default();
+
+        assert_eq!(names.unique("Female"), "Female");
+        assert_eq!(names.unique("female"), "female (2)");
+        assert_eq!(names.unique("Female"), "Female (3)");
+        assert_eq!(names.unique("Ball/Korb?"), "Ball-Korb-");
+        assert_eq!(
+            names.unique("A game with a really long name, longer than allowed"),
+            "A game with a really long name,"
+        );
+        assert_eq!(
+            names.unique("A game with a really long name, longer than allowed"),
+            "A game with a really long n (2)"
+        );
+    }
+
+    #[test]
+    fn game_rows_keep_data_that_cant_be_parsed() {
+        let game = Game {
+            id: Uuid::now_v7(),
+            trophy_id: 1,
+            name: "Lauf".to_string(),
+            kind: GameKind::Time,
+            scoring: ScoringKind::Descending,
+            attempts: 1,
+            aggregation: AttemptAggregation::Sum,
+            closed: true,
+            year: 2024,
+        };
+        let teams: Vec<Team> = (1..=3)
+            .map(|trophy_id| Team {
+                id: Uuid::now_v7(),
+                trophy_id,
+                name: format!("Team {}", trophy_id),
+                gender: TeamGender::Female,
+                category_id: Uuid::nil(),
+                points: 0,
+                year: 2024,
+            })
+            .collect();
+        let outcomes: Vec<Outcome> = teams
+            .iter()
+            .zip(["1:40", "kaputt", "1:30"])
+            .map(|(team, data)| Outcome {
+                game_id: game.id,
+                game_trophy_id: game.trophy_id,
+                game_name: game.name.clone(),
+                game_kind: GameKind::Time,
+                team_id: team.id,
+                team_trophy_id: team.trophy_id,
+                team_name: team.name.clone(),
+                team_gender: TeamGender::Female,
+                data: Some(data.to_string()),
+                value_integer: None,
+                value_millis: None,
+                value_millimetres: None,
+                status: OutcomeStatus::Ok,
+                penalty_points: None,
+                penalty_millis: None,
+                point_value: None,
+                version: 1,
+                override_reason: None,
+            })
+            .collect();
+        let results = Results {
+            year: 2024,
+            provisional: true,
+            games: vec![game.name.clone()],
+            teams: teams
+                .iter()
+                .map(|team| ResultRow {
+                    place: 1,
+                    team_id: team.id,
+                    trophy_id: team.trophy_id,
+                    team: team.name.clone(),
+                    category: "Female".to_string(),
+                    adjustments: 0,
+                    total: 0,
+                    games: Vec::new(),
+                })
+                .collect(),
+        };
+        let teams = teams.into_iter().map(|team| (team.id, team)).collect();
+
+        let rows = game_rows(
+            &game,
+            &outcomes,
+            &teams,
+            &results,
+            &TrophyConfig::default_for(2024),
+            Lang::En,
+        );
+
+        let rows: Vec<(Option<i32>, &str, &str)> = rows
+            .iter()
+            .map(|row| (row.place, row.team.as_str(), row.value.as_str()))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (Some(1), "Team 3", "1:30"),
+                (Some(2), "Team 1", "1:40"),
+                (None, "Team 2", "kaputt"),
+            ]
+        );
+    }
+}
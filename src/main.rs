@@ -18,10 +18,17 @@ use crate::{
     model::{CreateUser, User},
 };
 
+mod check;
 mod eval;
+mod export;
 mod middleware;
 mod model;
+mod pdf;
+mod ranking;
 mod routes;
+mod scoring;
+#[cfg(test)]
+mod test_db;
 
 #[actix_web::main]
 async fn main() -> Result<(), CustomError> {
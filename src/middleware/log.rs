@@ -16,6 +16,10 @@ use sqlx::PgPool;
 use std::{fmt::Display, rc::Rc};
 use uuid::Uuid;
 
+/// Details a handler adds to the history entry of its request, e.g. which values it has changed.
+/// Handlers insert it into the extensions of the request, the entry is written once the request has been handled.
+pub struct OperationDetail(pub String);
+
 pub struct LogMiddleware<S> {
     pool: Rc<Data<PgPool>>,
     service: Rc<S>,
@@ -86,9 +90,18 @@ where
                 None => (res.request().path().to_owned(), None),
             };
 
+            let detail = res
+                .request()
+                .extensions()
+                .get::<OperationDetail>()
+                .map(|detail| detail.0.clone());
+
             match match_operation(res.request().method(), &path) {
                 Err(err) => warn!("Could not extract operation-summary: {}", err),
-                Ok(summary) => {
+                Ok(mut summary) => {
+                    if let Some(detail) = detail {
+                        summary.operation = format!("{}: {}", summary.operation, detail);
+                    }
                     match auth {
                         Some(val) => {
                             let entry = History::create(
@@ -189,6 +202,15 @@ impl OperationSummary {
         }
     }
 
+    /// Changes to point adjustments alter the totals directly, so they are always logged as warnings.
+    fn adjust(operation: &str) -> Self {
+        OperationSummary {
+            operation: operation.to_string(),
+            subject_type: SubjectType::Adjustment,
+            level: LogLevel::Warn,
+        }
+    }
+
     fn import(subject_type: SubjectType) -> Self {
         OperationSummary {
             operation: "import".to_string(),
@@ -214,14 +236,80 @@ fn match_operation(method: &Method, path: &str) -> ApiResult<OperationSummary> {
             "evaluate trophy".to_string(),
             LogLevel::Warn,
         )),
+        "/eval/recompute" => Ok(OperationSummary::eval(
+            "recompute trophy".to_string(),
+            LogLevel::Warn,
+        )),
+        "/eval/check" => Ok(OperationSummary::eval(
+            "check trophy before evaluating".to_string(),
+            LogLevel::Debug,
+        )),
+        "/eval/preview" => Ok(OperationSummary::eval(
+            "preview evaluation".to_string(),
+            LogLevel::Debug,
+        )),
         "/eval/sheet" => Ok(OperationSummary::eval(
             "download sheet".to_string(),
             LogLevel::Debug,
         )),
+        "/eval/results.csv" => Ok(OperationSummary::eval(
+            "download results as csv".to_string(),
+            LogLevel::Debug,
+        )),
+        "/eval/results.json" => Ok(OperationSummary::eval(
+            "get results as json".to_string(),
+            LogLevel::Debug,
+        )),
+        "/eval/pdf/results" => Ok(OperationSummary::eval(
+            "download results as pdf".to_string(),
+            LogLevel::Debug,
+        )),
+        "/eval/pdf/certificates" => Ok(OperationSummary::eval(
+            "download certificates".to_string(),
+            LogLevel::Debug,
+        )),
+        "/eval/pdf/certificates/{id}" => Ok(OperationSummary::eval(
+            "download certificate of a team".to_string(),
+            LogLevel::Debug,
+        )),
         "/eval/done" => Ok(OperationSummary::eval(
             "check if evaluation is done".to_string(),
             LogLevel::Debug,
         )),
+        "/adjustments" => match *method {
+            Method::GET => Ok(OperationSummary::get_all(SubjectType::Adjustment)),
+            Method::POST => Ok(OperationSummary::adjust("create")),
+            _ => Err(CustomError::UnsupportedMethod {
+                method: method.to_string(),
+                path: path.to_string(),
+            }),
+        },
+        "/adjustments/{id}" => match *method {
+            Method::GET => Ok(OperationSummary::get(SubjectType::Adjustment)),
+            Method::PUT => Ok(OperationSummary::adjust("update")),
+            Method::DELETE => Ok(OperationSummary::adjust("delete")),
+            _ => Err(CustomError::UnsupportedMethod {
+                method: method.to_string(),
+                path: path.to_string(),
+            }),
+        },
+        "/categories" => match *method {
+            Method::GET => Ok(OperationSummary::get_all(SubjectType::Category)),
+            Method::POST => Ok(OperationSummary::create(SubjectType::Category)),
+            _ => Err(CustomError::UnsupportedMethod {
+                method: method.to_string(),
+                path: path.to_string(),
+            }),
+        },
+        "/categories/{id}" => match *method {
+            Method::GET => Ok(OperationSummary::get(SubjectType::Category)),
+            Method::PUT => Ok(OperationSummary::update(SubjectType::Category)),
+            Method::DELETE => Ok(OperationSummary::delete(SubjectType::Category)),
+            _ => Err(CustomError::UnsupportedMethod {
+                method: method.to_string(),
+                path: path.to_string(),
+            }),
+        },
         "/games" => match *method {
             Method::GET => Ok(OperationSummary::get_all(SubjectType::Game)),
             Method::POST => Ok(OperationSummary::create(SubjectType::Game)),
@@ -246,7 +334,40 @@ fn match_operation(method: &Method, path: &str) -> ApiResult<OperationSummary> {
             subject_type: SubjectType::Game,
             level: LogLevel::Debug,
         }),
+        "/games/{id}/close" => Ok(OperationSummary {
+            operation: "close game".to_string(),
+            subject_type: SubjectType::Game,
+            level: LogLevel::Info,
+        }),
+        "/games/{id}/reopen" => Ok(OperationSummary {
+            operation: "reopen game".to_string(),
+            subject_type: SubjectType::Game,
+            level: LogLevel::Warn,
+        }),
+        "/games/{id}/outcomes" => Ok(OperationSummary {
+            operation: "update outcomes of game".to_string(),
+            subject_type: SubjectType::Outcome,
+            level: LogLevel::Info,
+        }),
+        "/games/{id}/eval" => Ok(OperationSummary::eval(
+            "evaluate game".to_string(),
+            LogLevel::Warn,
+        )),
         "/history" => Ok(OperationSummary::get_all(SubjectType::History)),
+        "/years/{year}/config" => match *method {
+            Method::GET => Ok(OperationSummary::eval(
+                "get trophy-config".to_string(),
+                LogLevel::Debug,
+            )),
+            Method::PUT => Ok(OperationSummary::eval(
+                "update trophy-config".to_string(),
+                LogLevel::Warn,
+            )),
+            _ => Err(CustomError::UnsupportedMethod {
+                method: method.to_string(),
+                path: path.to_string(),
+            }),
+        },
         "/import" => Ok(OperationSummary::import(SubjectType::Team)),
         "/ping" => Ok(OperationSummary {
             operation: "ping".to_string(),
@@ -267,7 +388,28 @@ fn match_operation(method: &Method, path: &str) -> ApiResult<OperationSummary> {
             }),
         },
         "/outcomes/games/{id}" => Ok(OperationSummary::get_all(SubjectType::Outcome)),
+        "/outcomes/games/{id}/attempts" => match *method {
+            Method::GET => Ok(OperationSummary::get_all(SubjectType::Outcome)),
+            Method::PUT => Ok(OperationSummary {
+                operation: "set attempt".to_string(),
+                subject_type: SubjectType::Outcome,
+                level: LogLevel::Debug,
+            }),
+            _ => Err(CustomError::UnsupportedMethod {
+                method: method.to_string(),
+                path: path.to_string(),
+            }),
+        },
+        "/outcomes/games/{id}/points" => Ok(OperationSummary {
+            operation: "override point value".to_string(),
+            subject_type: SubjectType::Outcome,
+            level: LogLevel::Warn,
+        }),
         "/outcomes/teams/{id}" => Ok(OperationSummary::get_all(SubjectType::Outcome)),
+        "/standings" => Ok(OperationSummary::eval(
+            "get standings".to_string(),
+            LogLevel::Debug,
+        )),
         "/teams" => match *method {
             Method::GET => Ok(OperationSummary::get_all(SubjectType::Team)),
             Method::POST => Ok(OperationSummary::create(SubjectType::Team)),
@@ -0,0 +1,354 @@
+use super::{Game, Outcome, TypeInfo, UserRole, Value};
+use crate::{ApiResult, model::CustomError};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::fmt::{self, Display};
+use uuid::Uuid;
+
+/// A single attempt of a team in a [Game] with more than one.
+/// Once all attempts are in, their aggregate becomes the data of the team's [Outcome].
+#[derive(Serialize, FromRow)]
+pub struct Attempt {
+    pub game_id: Uuid,
+    pub team_id: Uuid,
+    /// Starts at 1.
+    pub number: i32,
+    pub data: String,
+}
+
+#[derive(Serialize)]
+pub struct AttemptVec(pub Vec<Attempt>);
+
+#[derive(Deserialize)]
+pub struct SetAttempt {
+    pub team_id: Uuid,
+    pub number: i32,
+    /// Blank data removes the attempt.
+    pub data: Option<String>,
+    /// The version of the team's [Outcome] the attempt is based on, always required but may be sent as `If-Match`-header instead.
+    pub version: Option<i32>,
+}
+
+impl Attempt {
+    /// Find all [Attempt]s for the specified [Game].
+    pub async fn find_all_for_game(game_id: Uuid, pool: &PgPool) -> ApiResult<AttemptVec> {
+        let attempts = sqlx::query_as!(
+            Attempt,
+            r#"SELECT game_id, team_id, number, data FROM attempts WHERE game_id = $1 ORDER BY team_id, number"#,
+            game_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(AttemptVec(attempts))
+    }
+
+    /// Store a single attempt after validating it against the kind of the [Game], then aggregate the attempts of the team again.
+    /// Returns the team's [Outcome], which only has data once all attempts are in.
+    /// Fails with a [CustomError::ConflictError] containing the current [Outcome] if its version differs from the sent one.
+    /// Referees can't set attempts once the game has been closed, see [Game::find_for_writing].
+    pub async fn set(
+        game_id: Uuid,
+        attempt: SetAttempt,
+        role: &UserRole,
+        pool: &PgPool,
+    ) -> ApiResult<Outcome> {
+        let Some(version) = attempt.version else {
+            return Err(CustomError::ValidationError {
+                message: "The version of the outcome is missing, send it in the body or as If-Match-header.".to_string(),
+            });
+        };
+        let mut tx = pool.begin().await?;
+        let game = Game::find_for_writing(game_id, role, &mut tx).await?;
+        if game.attempts < 2 {
+            return Err(CustomError::ValidationError {
+                message: format!(
+                    "Game {} only has a single attempt, set the data of the outcome instead.",
+                    game.name
+                ),
+            });
+        }
+        if attempt.number < 1 || attempt.number > game.attempts {
+            return Err(CustomError::ValidationError {
+                message: format!(
+                    "Game {} has attempts 1 to {}, not {}.",
+                    game.name, game.attempts, attempt.number
+                ),
+            });
+        }
+
+        match attempt.data.as_deref().map(str::trim) {
+            Some(data) if !data.is_empty() => {
+                // attempts are stored in their normalised form just like outcomes
+                let data = Value::parse(data, &game.kind)?.to_string();
+                sqlx::query!(
+                    r#"INSERT INTO attempts (game_id, team_id, number, data) VALUES ($1, $2, $3, $4)
+                        ON CONFLICT (game_id, team_id, number) DO UPDATE SET data = $4"#,
+                    game.id,
+                    attempt.team_id,
+                    attempt.number,
+                    data
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+            _ => {
+                sqlx::query!(
+                    r#"DELETE FROM attempts WHERE game_id = $1 AND team_id = $2 AND number = $3"#,
+                    game.id,
+                    attempt.team_id,
+                    attempt.number
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        let value = Attempt::aggregate(&game, attempt.team_id, &mut tx).await?;
+        let outcome =
+            Outcome::store_value(game.id, attempt.team_id, value, version, &mut tx).await?;
+
+        tx.commit().await?;
+        Ok(outcome)
+    }
+
+    /// Combine the attempts of the team by the aggregation of the [Game].
+    /// Returns `None` until all attempts are in, so the outcome stays pending.
+    /// Like [Outcome::create], this runs as part of the caller's transaction.
+    pub async fn aggregate(
+        game: &Game,
+        team_id: Uuid,
+        connection: &mut PgConnection,
+    ) -> ApiResult<Option<Value>> {
+        let attempts = sqlx::query_scalar!(
+            r#"SELECT data FROM attempts WHERE game_id = $1 AND team_id = $2 ORDER BY number"#,
+            game.id,
+            team_id
+        )
+        .fetch_all(connection)
+        .await?;
+
+        Attempt::combine(game, team_id, attempts)
+    }
+
+    /// Combine the data of the attempts, ordered by number - see [Attempt::aggregate].
+    fn combine(game: &Game, team_id: Uuid, attempts: Vec<String>) -> ApiResult<Option<Value>> {
+        if (attempts.len() as i32) < game.attempts {
+            return Ok(None);
+        }
+
+        let mut values = Vec::<Value>::new();
+        for data in attempts {
+            match Value::parse(&data, &game.kind) {
+                Ok(value) => values.push(value),
+                Err(CustomError::ValidationError { message }) => {
+                    return Err(CustomError::ValidationError {
+                        message: format!(
+                            "An attempt of team {} doesn't fit game {} anymore: {}",
+                            team_id, game.name, message
+                        ),
+                    });
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(Value::aggregate(values, game.aggregation))
+    }
+
+    /// Align the stored attempts with the (possibly changed) amount of attempts of the [Game].
+    /// Data entered while the game had a single attempt becomes the first one, surplus attempts are removed.
+    /// Like [Outcome::create], this runs as part of the caller's transaction.
+    pub async fn fit_to_game(game: &Game, connection: &mut PgConnection) -> ApiResult<()> {
+        if game.attempts > 1 {
+            sqlx::query!(
+                r#"INSERT INTO attempts (game_id, team_id, number, data)
+                    SELECT game_id, team_id, 1, data FROM game_team
+                    WHERE game_id = $1 AND data IS NOT NULL
+                    AND NOT EXISTS (SELECT 1 FROM attempts WHERE attempts.game_id = game_team.game_id AND attempts.team_id = game_team.team_id)"#,
+                game.id
+            )
+            .execute(&mut *connection)
+            .await?;
+        }
+
+        // a single attempt is stored as the outcome's data only
+        sqlx::query!(
+            r#"DELETE FROM attempts WHERE game_id = $1 AND (number > $2 OR $2 = 1)"#,
+            game.id,
+            game.attempts
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl Display for Attempt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Attempt(game_id: {}, team_id: {}, number: {}, data: {})",
+            self.game_id, self.team_id, self.number, self.data
+        )
+    }
+}
+
+impl TypeInfo for Attempt {
+    fn type_name(&self) -> String {
+        "Attempt".to_string()
+    }
+}
+
+impl Display for AttemptVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "AttemptVec[{}]",
+            self.0.iter().map(|a| a.to_string()).collect::<String>()
+        )
+    }
+}
+
+impl TypeInfo for AttemptVec {
+    fn type_name(&self) -> String {
+        "AttemptVec".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{AttemptAggregation, CreateGame, GameKind, ScoringKind, TeamGender};
+    use crate::test_db::TestYear;
+    use actix_web::{ResponseError, http::StatusCode};
+
+    fn get_game(attempts: i32) -> Game {
+        Game {
+            id: Uuid::now_v7(),
+            trophy_id: 1,
+            name: "Kegeln".to_string(),
+            kind: GameKind::Points,
+            scoring: ScoringKind::Descending,
+            attempts,
+            aggregation: AttemptAggregation::Sum,
+            closed: false,
+            year: 2024,
+        }
+    }
+
+    fn data(attempts: &[&str]) -> Vec<String> {
+        attempts.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn outcome_stays_pending_until_all_attempts_are_in() {
+        let game = get_game(3);
+
+        assert_eq!(
+            Attempt::combine(&game, Uuid::nil(), Vec::new()).unwrap(),
+            None
+        );
+        assert_eq!(
+            Attempt::combine(&game, Uuid::nil(), data(&["4", "5"])).unwrap(),
+            None
+        );
+        assert_eq!(
+            Attempt::combine(&game, Uuid::nil(), data(&["4", "5", "6"])).unwrap(),
+            Some(Value::Points(15))
+        );
+    }
+
+    #[test]
+    fn attempts_that_dont_fit_the_game_fail() {
+        let game = get_game(2);
+
+        assert!(matches!(
+            Attempt::combine(&game, Uuid::nil(), data(&["4", "1:30"])),
+            Err(CustomError::ValidationError { .. })
+        ));
+    }
+
+    /// Needs a database with the current schema at `DATABASE_URL`, run with `cargo test -- --ignored`.
+    #[actix_web::test]
+    #[ignore = "requires a database at DATABASE_URL"]
+    async fn more_attempts_keep_the_data_as_first_attempt() {
+        let test_year = TestYear::new().await;
+        let pool = &test_year.pool;
+
+        let create_game = |attempts| CreateGame {
+            attempts,
+            ..test_year.create_game(1, "Kegeln")
+        };
+        let game = test_year.game(create_game(1)).await;
+        let team = test_year.team(1, TeamGender::Female).await;
+        sqlx::query("UPDATE game_team SET data = '10' WHERE game_id = $1")
+            .bind(game.id)
+            .execute(pool)
+            .await
+            .unwrap();
+
+        let game = Game::update(game.id, create_game(3), pool).await.unwrap();
+        let attempts = Attempt::find_all_for_game(game.id, pool).await.unwrap().0;
+        let mut connection = pool.acquire().await.unwrap();
+        let pending = Attempt::aggregate(&game, team.id, &mut connection)
+            .await
+            .unwrap();
+        let outcome = Outcome::find(game.id, team.id, &mut connection)
+            .await
+            .unwrap();
+
+        drop(connection);
+        test_year.clean_up().await;
+
+        let attempts: Vec<(i32, String)> =
+            attempts.into_iter().map(|a| (a.number, a.data)).collect();
+        assert_eq!(attempts, vec![(1, "10".to_string())]);
+        assert_eq!(pending, None);
+        // the outcome waits for the two missing attempts
+        assert_eq!(outcome.data, None);
+    }
+
+    /// Needs a database with the current schema at `DATABASE_URL`, run with `cargo test -- --ignored`.
+    #[actix_web::test]
+    #[ignore = "requires a database at DATABASE_URL"]
+    async fn attempts_on_a_stale_version_get_the_current_outcome() {
+        let test_year = TestYear::new().await;
+        let pool = &test_year.pool;
+
+        let game = CreateGame {
+            attempts: 2,
+            ..test_year.create_game(1, "Kegeln")
+        };
+        let game = test_year.game(game).await;
+        let team = test_year.team(1, TeamGender::Female).await;
+        let attempt = |number, version| SetAttempt {
+            team_id: team.id,
+            number,
+            data: Some("4".to_string()),
+            version,
+        };
+
+        let first = Attempt::set(game.id, attempt(1, Some(0)), &UserRole::Admin, pool).await;
+        let missing = Attempt::set(game.id, attempt(2, None), &UserRole::Admin, pool).await;
+        // based on the version before the first attempt
+        let stale = Attempt::set(game.id, attempt(2, Some(0)), &UserRole::Admin, pool).await;
+        let attempts = Attempt::find_all_for_game(game.id, pool).await.unwrap().0;
+
+        test_year.clean_up().await;
+
+        assert_eq!(first.unwrap().version, 1);
+        assert!(matches!(missing, Err(CustomError::ValidationError { .. })));
+        let stale = stale.err().unwrap();
+        assert_eq!(stale.status_code(), StatusCode::CONFLICT);
+        let body = actix_web::body::to_bytes(stale.error_response().into_body())
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["current"]["team_id"], team.id.to_string());
+        assert_eq!(body["current"]["version"], 1);
+        // the rejected attempt has been rolled back
+        assert_eq!(attempts.len(), 1);
+    }
+}
@@ -0,0 +1,344 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::fmt::{self, Display};
+use uuid::Uuid;
+
+use super::{CustomError, TeamGender, TypeInfo};
+use crate::ApiResult;
+
+/// [Team](super::Team)s are ranked separately for each category, e.g. by gender or age group.
+/// Categories belong to a single year, `position` orders them in exports.
+#[derive(Serialize, FromRow, Clone)]
+pub struct Category {
+    pub id: Uuid,
+    pub name: String,
+    pub position: i32,
+    pub year: i32,
+}
+
+#[derive(Serialize)]
+pub struct CategoryVec(pub Vec<Category>);
+
+#[derive(Deserialize)]
+pub struct CreateCategory {
+    pub name: String,
+    #[serde(default)]
+    pub position: i32,
+    pub year: i32,
+}
+
+impl Category {
+    /// Find all [Category]s of the specified year in the order they should be displayed in.
+    pub async fn find_all(pool: &PgPool, year: i32) -> ApiResult<CategoryVec> {
+        let categories = sqlx::query_as!(
+            Category,
+            r#"SELECT id, name, position, year FROM categories WHERE year = $1 ORDER BY position, name"#,
+            year
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(CategoryVec(categories))
+    }
+
+    /// Try to get the [Category] of the specified ID.
+    pub async fn find(id: Uuid, connection: &mut PgConnection) -> ApiResult<Category> {
+        let category = sqlx::query_as!(
+            Category,
+            r#"SELECT id, name, position, year FROM categories WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(connection)
+        .await?;
+
+        category.ok_or(CustomError::NotFoundError {
+            message: format!("Category {} could not be found.", id),
+        })
+    }
+
+    /// Find the [Category] of the specified name or create it if it doesn't exist yet.
+    /// Like [Outcome::create](super::Outcome::create), this runs as part of the caller's transaction.
+    pub async fn find_or_create(
+        name: &str,
+        year: i32,
+        connection: &mut PgConnection,
+    ) -> ApiResult<Category> {
+        // the no-op update makes RETURNING work for already existing categories
+        let category = sqlx::query_as!(
+            Category,
+            r#"INSERT INTO categories (id, name, year)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (year, name) DO UPDATE SET name = EXCLUDED.name
+                RETURNING id, name, position, year"#,
+            Uuid::now_v7(),
+            name,
+            year
+        )
+        .fetch_one(connection)
+        .await?;
+
+        Ok(category)
+    }
+
+    /// Resolve the [Category] a team of the specified year should be assigned to.
+    /// Without an explicit category, teams are assigned to the category named after their gender - this
+    /// keeps clients working that don't know about categories.
+    /// Like [Outcome::create](super::Outcome::create), this runs as part of the caller's transaction, so a category is only created along with its team.
+    pub async fn resolve(
+        category_id: Option<Uuid>,
+        gender: &TeamGender,
+        year: i32,
+        connection: &mut PgConnection,
+    ) -> ApiResult<Category> {
+        match category_id {
+            Some(id) => Category::find_in_year(id, year, connection).await,
+            None => Category::find_or_create(&gender.to_string(), year, connection).await,
+        }
+    }
+
+    /// Try to get the [Category] of the specified ID, failing if it belongs to a different year.
+    pub async fn find_in_year(
+        id: Uuid,
+        year: i32,
+        connection: &mut PgConnection,
+    ) -> ApiResult<Category> {
+        let category = Category::find(id, connection).await?;
+
+        if category.year != year {
+            return Err(CustomError::ValidationError {
+                message: format!(
+                    "Category {} belongs to {}, not to {}.",
+                    category.name, category.year, year
+                ),
+            });
+        }
+
+        Ok(category)
+    }
+
+    /// Create a new [Category].
+    pub async fn create(create_category: CreateCategory, pool: &PgPool) -> ApiResult<Category> {
+        let mut tx = pool.begin().await?;
+        let category = sqlx::query_as!(
+            Category,
+            r#"INSERT INTO categories (id, name, position, year)
+                VALUES ($1, $2, $3, $4)
+                RETURNING id, name, position, year"#,
+            Uuid::now_v7(),
+            create_category.name,
+            create_category.position,
+            create_category.year
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(category)
+    }
+
+    /// Update the specified [Category].
+    pub async fn update(
+        id: Uuid,
+        altered_category: CreateCategory,
+        pool: &PgPool,
+    ) -> ApiResult<Category> {
+        // NOTE like teams and games, categories can't be moved to another year
+        let mut tx = pool.begin().await?;
+        let category = sqlx::query_as!(
+            Category,
+            r#"UPDATE categories SET name = $1, position = $2 WHERE id = $3 RETURNING id, name, position, year"#,
+            altered_category.name,
+            altered_category.position,
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(category)
+    }
+
+    /// Delete the specified [Category]. Fails while teams are still assigned to it.
+    pub async fn delete(id: Uuid, pool: &PgPool) -> ApiResult<Category> {
+        let mut tx = pool.begin().await?;
+        let teams = sqlx::query_scalar!(r#"SELECT COUNT(*) FROM teams WHERE category_id = $1"#, id)
+            .fetch_one(&mut *tx)
+            .await?
+            .unwrap_or(0);
+
+        if teams > 0 {
+            return Err(CustomError::ValidationError {
+                message: format!(
+                    "Category {} still has {} teams, reassign them first.",
+                    id, teams
+                ),
+            });
+        }
+
+        let category = sqlx::query_as!(
+            Category,
+            r#"DELETE FROM categories WHERE id = $1 RETURNING id, name, position, year"#,
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(category)
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Category(id: {}, name: {}, position: {}, year: {})",
+            self.id, self.name, self.position, self.year
+        )
+    }
+}
+
+impl TypeInfo for Category {
+    fn type_name(&self) -> String {
+        "Category".to_string()
+    }
+}
+
+impl Display for CategoryVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "CategoryVec[{}]",
+            self.0.iter().map(|g| g.to_string()).collect::<String>()
+        )
+    }
+}
+
+impl TypeInfo for CategoryVec {
+    fn type_name(&self) -> String {
+        "CategoryVec".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        model::{CreateTeam, Team},
+        test_db::TestYear,
+    };
+
+    /// Needs a database with the current schema at `DATABASE_URL`, run with `cargo test -- --ignored`.
+    #[actix_web::test]
+    #[ignore = "requires a database at DATABASE_URL"]
+    async fn teams_without_category_get_the_one_of_their_gender() {
+        let test_year = TestYear::new().await;
+        let (pool, year) = (&test_year.pool, test_year.year);
+
+        let first = test_year.team(1, TeamGender::Female).await;
+        let second = test_year.team(2, TeamGender::Female).await;
+        let third = test_year.team(3, TeamGender::Male).await;
+        let categories = Category::find_all(pool, year).await.unwrap().0;
+        let grouped = Team::find_all_by_category(pool, year).await.unwrap();
+        let mut connection = pool.acquire().await.unwrap();
+        let elsewhere = Category::resolve(
+            Some(first.category_id),
+            &TeamGender::Female,
+            year + 1,
+            &mut connection,
+        )
+        .await;
+
+        drop(connection);
+        test_year.clean_up().await;
+
+        let names: Vec<&str> = categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Female", "Male"]);
+        assert_eq!(first.category_id, second.category_id);
+        assert_ne!(first.category_id, third.category_id);
+        let sizes: Vec<usize> = grouped.iter().map(|c| c.teams.0.len()).collect();
+        assert_eq!(sizes, vec![2, 1]);
+        assert!(matches!(
+            elsewhere,
+            Err(CustomError::ValidationError { .. })
+        ));
+    }
+
+    /// Needs a database with the current schema at `DATABASE_URL`, run with `cargo test -- --ignored`.
+    #[actix_web::test]
+    #[ignore = "requires a database at DATABASE_URL"]
+    async fn failed_teams_leave_no_category_behind() {
+        let test_year = TestYear::new().await;
+        let (pool, year) = (&test_year.pool, test_year.year);
+
+        // names are limited to 50 characters, so the team itself can't be inserted
+        let team = CreateTeam {
+            trophy_id: 1,
+            name: "x".repeat(60),
+            gender: TeamGender::Mixed,
+            category_id: None,
+            year,
+        };
+        let result = Team::create(team, pool).await;
+        let categories = Category::find_all(pool, year).await.unwrap().0;
+
+        test_year.clean_up().await;
+
+        assert!(result.is_err());
+        assert!(categories.is_empty());
+    }
+
+    /// Needs a database at `DATABASE_URL`, run with `cargo test -- --ignored`.
+    /// The migration runs in a schema of its own within a transaction that is rolled back afterwards.
+    #[actix_web::test]
+    #[ignore = "requires a database at DATABASE_URL"]
+    async fn migration_assigns_existing_teams_to_their_gender() {
+        let test_year = TestYear::new().await;
+        let mut tx = test_year.pool.begin().await.unwrap();
+
+        sqlx::raw_sql(
+            "CREATE SCHEMA category_migration;
+            SET LOCAL search_path TO category_migration, public;
+            CREATE TABLE teams (id uuid PRIMARY KEY NOT NULL, gender team_gender NOT NULL, year integer NOT NULL);
+            INSERT INTO teams VALUES
+                (gen_random_uuid(), 'female', 2023),
+                (gen_random_uuid(), 'male', 2023),
+                (gen_random_uuid(), 'female', 2024),
+                (gen_random_uuid(), 'female', 2024);",
+        )
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        // the subject type already exists in the current schema
+        let migration: String = include_str!("../../db/add-categories.sql")
+            .lines()
+            .filter(|line| !line.starts_with("ALTER TYPE"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        sqlx::raw_sql(sqlx::AssertSqlSafe(migration))
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        let assigned: Vec<(i32, String, i64)> = sqlx::query_as(
+            "SELECT teams.year, categories.name, COUNT(*) FROM teams
+                INNER JOIN categories ON teams.category_id = categories.id AND teams.year = categories.year
+                GROUP BY teams.year, categories.name ORDER BY teams.year, categories.name",
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .unwrap();
+
+        tx.rollback().await.unwrap();
+        test_year.clean_up().await;
+
+        assert_eq!(
+            assigned,
+            vec![
+                (2023, "Female".to_string(), 1),
+                (2023, "Male".to_string(), 1),
+                (2024, "Female".to_string(), 2),
+            ]
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::fmt::{self, Display};
+use uuid::Uuid;
+
+use super::{CustomError, Game, Lang, TypeInfo};
+use crate::ApiResult;
+
+/// The points the winner of a game receives if a year has not been configured.
+pub const MAX_POINTS: i32 = 50;
+
+/// The least amount of points an outcome receives if a year has not been configured.
+pub const MIN_POINTS: i32 = 1;
+
+/// Decides which place follows a tie.
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Copy, PartialEq, Debug)]
+#[sqlx(type_name = "place_gap")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum PlaceGap {
+    /// Skip the shared places, e.g. 1, 2, 2, 4.
+    Skip,
+    /// Continue with the next place, e.g. 1, 2, 2, 3.
+    Dense,
+}
+
+impl fmt::Display for PlaceGap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlaceGap::Skip => write!(f, "Skip"),
+            PlaceGap::Dense => write!(f, "Dense"),
+        }
+    }
+}
+
+/// Decides how many points teams receive that didn't start or didn't finish a game.
+/// Disqualified teams always receive zero points.
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Copy, PartialEq, Debug, Default)]
+#[sqlx(type_name = "non_finisher_scoring")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum NonFinisherScoring {
+    /// No points at all, not even [TrophyConfig::min_points].
+    #[default]
+    Zero,
+    /// The points of the place after the last team that finished.
+    Last,
+}
+
+impl fmt::Display for NonFinisherScoring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NonFinisherScoring::Zero => write!(f, "Zero"),
+            NonFinisherScoring::Last => write!(f, "Last"),
+        }
+    }
+}
+
+/// Breaks ties between teams with equal totals, applied in the configured order.
+/// All rules are computed from the stored point values, ranked within the team's category.
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Copy, PartialEq, Debug)]
+#[sqlx(type_name = "tie_breaker")]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreaker {
+    /// More games won.
+    Wins,
+    /// Better place in the team's best game.
+    BestPlacement,
+    /// More points in [TrophyConfig::tiebreaker_game_id].
+    Game,
+}
+
+impl fmt::Display for TieBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TieBreaker::Wins => write!(f, "Wins"),
+            TieBreaker::BestPlacement => write!(f, "BestPlacement"),
+            TieBreaker::Game => write!(f, "Game"),
+        }
+    }
+}
+
+/// The tie-break rules of the Klostertrophy.
+pub const DEFAULT_TIE_BREAKERS: [TieBreaker; 3] = [
+    TieBreaker::Wins,
+    TieBreaker::BestPlacement,
+    TieBreaker::Game,
+];
+
+/// The placeholders a certificate template may contain.
+pub const CERTIFICATE_PLACEHOLDERS: [&str; 5] = ["team", "category", "place", "points", "year"];
+
+/// Configures how points are assigned for a single year.
+/// Years without a stored configuration use [MAX_POINTS], [MIN_POINTS] and [PlaceGap::Skip].
+#[derive(Serialize, FromRow, Clone)]
+pub struct TrophyConfig {
+    pub year: i32,
+    pub max_points: i32,
+    /// No outcome ever receives less than this, regardless of the scoring-strategy or the amount of teams.
+    pub min_points: i32,
+    pub gap: PlaceGap,
+    /// Teams with equal totals only share a place if none of these rules tells them apart.
+    pub tie_breakers: Vec<TieBreaker>,
+    pub tiebreaker_game_id: Option<Uuid>,
+    pub non_finishers: NonFinisherScoring,
+    /// One line of the certificate per line, the first one is the heading.
+    /// Placeholders like `{team}` are replaced per team, the default of the requested [Lang] is used if unset.
+    pub certificate_template: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateTrophyConfig {
+    pub max_points: i32,
+    pub min_points: i32,
+    pub gap: PlaceGap,
+    #[serde(default = "default_tie_breakers")]
+    pub tie_breakers: Vec<TieBreaker>,
+    #[serde(default)]
+    pub tiebreaker_game_id: Option<Uuid>,
+    #[serde(default)]
+    pub non_finishers: NonFinisherScoring,
+    #[serde(default)]
+    pub certificate_template: Option<String>,
+}
+
+fn default_tie_breakers() -> Vec<TieBreaker> {
+    DEFAULT_TIE_BREAKERS.to_vec()
+}
+
+impl TrophyConfig {
+    /// Get the configuration that applies if nothing has been stored for the specified year.
+    pub fn default_for(year: i32) -> TrophyConfig {
+        TrophyConfig {
+            year,
+            max_points: MAX_POINTS,
+            min_points: MIN_POINTS,
+            gap: PlaceGap::Skip,
+            tie_breakers: default_tie_breakers(),
+            tiebreaker_game_id: None,
+            non_finishers: NonFinisherScoring::Zero,
+            certificate_template: None,
+        }
+    }
+
+    /// Get the certificate template of the year, falling back to the default template of the [Lang].
+    /// A configured template is used for every [Lang], only the category names filled into it are translated.
+    pub fn certificate_template(&self, lang: Lang) -> &str {
+        self.certificate_template
+            .as_deref()
+            .unwrap_or(lang.labels().certificate_template)
+    }
+
+    /// Find the [TrophyConfig] for the specified year, falling back to [TrophyConfig::default_for].
+    pub async fn find(year: i32, connection: &mut PgConnection) -> ApiResult<TrophyConfig> {
+        let config = sqlx::query_as!(
+            TrophyConfig,
+            r#"SELECT year, max_points, min_points, gap as "gap: PlaceGap", tie_breakers as "tie_breakers: Vec<TieBreaker>", tiebreaker_game_id, non_finishers as "non_finishers: NonFinisherScoring", certificate_template FROM trophy_config WHERE year = $1"#,
+            year
+        )
+        .fetch_optional(connection)
+        .await?;
+
+        Ok(config.unwrap_or(TrophyConfig::default_for(year)))
+    }
+
+    /// Create or replace the [TrophyConfig] for the specified year.
+    pub async fn update(
+        year: i32,
+        altered_config: UpdateTrophyConfig,
+        pool: &PgPool,
+    ) -> ApiResult<TrophyConfig> {
+        if altered_config.min_points < 1 {
+            return Err(CustomError::ValidationError {
+                message: "The minimum of points must be at least 1.".to_string(),
+            });
+        }
+
+        if altered_config.max_points < altered_config.min_points {
+            return Err(CustomError::ValidationError {
+                message: format!(
+                    "The maximum of points ({}) must not be less than the minimum ({}).",
+                    altered_config.max_points, altered_config.min_points
+                ),
+            });
+        }
+
+        if let Some(game_id) = altered_config.tiebreaker_game_id {
+            let game = Game::find(game_id, pool).await?;
+            if game.year != year {
+                return Err(CustomError::ValidationError {
+                    message: format!(
+                        "The tiebreaker-game {} belongs to {}, not to {}.",
+                        game.name, game.year, year
+                    ),
+                });
+            }
+        }
+
+        if let Some(template) = &altered_config.certificate_template {
+            validate_certificate_template(template)?;
+        }
+
+        let mut tx = pool.begin().await?;
+        let config = sqlx::query_as!(
+            TrophyConfig,
+            r#"INSERT INTO trophy_config (year, max_points, min_points, gap, tie_breakers, tiebreaker_game_id, non_finishers, certificate_template)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (year) DO UPDATE SET max_points = $2, min_points = $3, gap = $4, tie_breakers = $5, tiebreaker_game_id = $6, non_finishers = $7, certificate_template = $8
+                RETURNING year, max_points, min_points, gap as "gap: PlaceGap", tie_breakers as "tie_breakers: Vec<TieBreaker>", tiebreaker_game_id, non_finishers as "non_finishers: NonFinisherScoring", certificate_template"#,
+            year,
+            altered_config.max_points,
+            altered_config.min_points,
+            altered_config.gap as PlaceGap,
+            &altered_config.tie_breakers as &[TieBreaker],
+            altered_config.tiebreaker_game_id,
+            altered_config.non_finishers as NonFinisherScoring,
+            altered_config.certificate_template
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(config)
+    }
+}
+
+/// Make sure the template has some text and only uses the known [CERTIFICATE_PLACEHOLDERS].
+fn validate_certificate_template(template: &str) -> ApiResult<()> {
+    if template.trim().is_empty() {
+        return Err(CustomError::ValidationError {
+            message: "The certificate template must not be empty.".to_string(),
+        });
+    }
+
+    for (start, _) in template.match_indices('{') {
+        let rest = &template[start + 1..];
+        let placeholder = rest.find('}').map(|end| &rest[..end]);
+        let message = match placeholder {
+            Some(p) if CERTIFICATE_PLACEHOLDERS.contains(&p) => continue,
+            Some(p) => format!(
+                "The certificate template contains the unknown placeholder {{{}}}",
+                p
+            ),
+            None => "The certificate template contains a '{' that is never closed".to_string(),
+        };
+        return Err(CustomError::ValidationError {
+            message: format!(
+                "{}, use one of {}.",
+                message,
+                CERTIFICATE_PLACEHOLDERS
+                    .map(|p| format!("{{{}}}", p))
+                    .join(", ")
+            ),
+        });
+    }
+
+    Ok(())
+}
+
+impl Display for TrophyConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "TrophyConfig(year: {}, max_points: {}, min_points: {}, gap: {}, tie_breakers: [{}], tiebreaker_game_id: {}, non_finishers: {}, certificate_template: {:?})",
+            self.year,
+            self.max_points,
+            self.min_points,
+            self.gap,
+            self.tie_breakers
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+            self.tiebreaker_game_id
+                .map(|id| id.to_string())
+                .unwrap_or("none".to_string()),
+            self.non_finishers,
+            self.certificate_template
+        )
+    }
+}
+
+impl TypeInfo for TrophyConfig {
+    fn type_name(&self) -> String {
+        "TrophyConfig".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn certificate_templates_only_use_known_placeholders() {
+        for lang in [Lang::De, Lang::En] {
+            assert!(validate_certificate_template(lang.labels().certificate_template).is_ok());
+        }
+        assert!(validate_certificate_template("Certificate\n{team}: {place}.").is_ok());
+        assert!(validate_certificate_template(" \n ").is_err());
+        assert!(validate_certificate_template("{team} from {town}").is_err());
+        assert!(validate_certificate_template("{team").is_err());
+    }
+}
@@ -5,11 +5,25 @@ use actix_web::{
 use argon2::password_hash;
 use serde::Serialize;
 use thiserror::Error;
+use uuid::Uuid;
 use xlsxwriter::XlsxError;
 
 #[derive(Serialize)]
 pub struct ErrorResponse {
     pub error: String,
+    /// The current state of a resource that has been changed in the meantime.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<serde_json::Value>,
+    /// The errors of the single rows of a bulk-request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rows: Option<Vec<RowError>>,
+}
+
+/// The error of a single row of a bulk-request, identified by its team.
+#[derive(Serialize, Debug, Clone)]
+pub struct RowError {
+    pub team_id: Uuid,
+    pub error: String,
 }
 
 /// This enables me to simply call err.error_response() on errors, so all errors
@@ -34,6 +48,20 @@ pub enum CustomError {
     AlreadyExistsError { message: String },
     #[error("No data when there should have been some: {message}")]
     NoDataSentError { message: String },
+    #[error("The sent data is invalid: {message}")]
+    ValidationError { message: String },
+    #[error("The resource has been changed in the meantime: {message}")]
+    ConflictError {
+        message: String,
+        current: serde_json::Value,
+    },
+    #[error("The sent data is invalid: {message}")]
+    BulkValidationError {
+        message: String,
+        rows: Vec<RowError>,
+    },
+    #[error("The game has already been closed: {message}")]
+    GameClosedError { message: String },
 
     // eval-errors
     #[error("You tried to evaluate while teams are still playing: {message}")]
@@ -70,6 +98,14 @@ impl error::ResponseError for CustomError {
     fn error_response(&self) -> HttpResponse {
         let response = ErrorResponse {
             error: self.to_string(),
+            current: match self {
+                CustomError::ConflictError { current, .. } => Some(current.clone()),
+                _ => None,
+            },
+            rows: match self {
+                CustomError::BulkValidationError { rows, .. } => Some(rows.clone()),
+                _ => None,
+            },
         };
 
         match serde_json::to_string(&response) {
@@ -95,6 +131,10 @@ impl error::ResponseError for CustomError {
             CustomError::ActixError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
             CustomError::HumanTimeError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
             CustomError::NoDataSentError { .. } => StatusCode::BAD_REQUEST,
+            CustomError::ValidationError { .. } => StatusCode::BAD_REQUEST,
+            CustomError::ConflictError { .. } => StatusCode::CONFLICT,
+            CustomError::BulkValidationError { .. } => StatusCode::BAD_REQUEST,
+            CustomError::GameClosedError { .. } => StatusCode::LOCKED,
 
             // eval-errors
             // 425 -> too early, experimental API!
@@ -1,17 +1,25 @@
-use super::{CustomError, Outcome, Team};
+use super::{CustomError, Outcome, Team, UserRole};
 use crate::{ApiResult, TypeInfo, model::Amount};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool};
+use sqlx::{FromRow, PgConnection, PgPool};
 use std::fmt::{self, Display};
 use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "game_kind")]
-#[sqlx(rename_all = "lowercase")]
-#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
 pub enum GameKind {
+    /// More points win.
     Points,
+    /// Less time wins, given in seconds.
     Time,
+    /// Longer distances win, given in metres with up to three decimals.
+    Distance,
+    /// Fewer points win, like in golf.
+    LowestPoints,
+    /// More hits win, less time decides between equal hits - given as `hits/seconds`.
+    HitsWithTime,
 }
 
 // Only return the name with no other information - this will be combined later.
@@ -19,7 +27,59 @@ impl fmt::Display for GameKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             GameKind::Points => write!(f, "Points"),
-            GameKind::Time => write!(f, "Points"),
+            GameKind::Time => write!(f, "Time"),
+            GameKind::Distance => write!(f, "Distance"),
+            GameKind::LowestPoints => write!(f, "LowestPoints"),
+            GameKind::HitsWithTime => write!(f, "HitsWithTime"),
+        }
+    }
+}
+
+/// Selects the [ScoringStrategy](crate::scoring::ScoringStrategy) used to assign points for a [Game].
+#[derive(Serialize, Deserialize, sqlx::Type, Default)]
+#[sqlx(type_name = "scoring_kind")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ScoringKind {
+    #[default]
+    Descending,
+    Linear,
+    Formula,
+    Podium,
+    Percentage,
+}
+
+impl fmt::Display for ScoringKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScoringKind::Descending => write!(f, "Descending"),
+            ScoringKind::Linear => write!(f, "Linear"),
+            ScoringKind::Formula => write!(f, "Formula"),
+            ScoringKind::Podium => write!(f, "Podium"),
+            ScoringKind::Percentage => write!(f, "Percentage"),
+        }
+    }
+}
+
+/// Combines the attempts of a team into the single value that is ranked, see [Value::aggregate](super::Value::aggregate).
+#[derive(Serialize, Deserialize, sqlx::Type, Clone, Copy, PartialEq, Debug, Default)]
+#[sqlx(type_name = "attempt_aggregation")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AttemptAggregation {
+    #[default]
+    Best,
+    Sum,
+    /// Rounded to whole points or hits, milliseconds and millimetres.
+    Average,
+}
+
+impl fmt::Display for AttemptAggregation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttemptAggregation::Best => write!(f, "Best"),
+            AttemptAggregation::Sum => write!(f, "Sum"),
+            AttemptAggregation::Average => write!(f, "Average"),
         }
     }
 }
@@ -30,6 +90,12 @@ pub struct Game {
     pub trophy_id: i32,
     pub name: String,
     pub kind: GameKind,
+    pub scoring: ScoringKind,
+    /// Games with more than one attempt receive their data via [Attempt](super::Attempt)s.
+    pub attempts: i32,
+    pub aggregation: AttemptAggregation,
+    /// Set by the referee once all outcomes are entered, afterwards only admins may change them.
+    pub closed: bool,
     pub year: i32,
 }
 
@@ -42,43 +108,82 @@ pub struct CreateGame {
     pub trophy_id: i32,
     pub name: String,
     pub kind: GameKind,
+    /// Falls back to [ScoringKind::Descending] so existing clients keep working.
+    #[serde(default)]
+    pub scoring: ScoringKind,
+    #[serde(default = "default_attempts")]
+    pub attempts: i32,
+    #[serde(default)]
+    pub aggregation: AttemptAggregation,
     pub year: i32,
 }
 
+fn default_attempts() -> i32 {
+    1
+}
+
+impl CreateGame {
+    fn validate(&self) -> ApiResult<()> {
+        if self.attempts < 1 {
+            return Err(CustomError::ValidationError {
+                message: "A game needs at least one attempt.".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
 impl Game {
     /// Find all [Game]s.
-    pub async fn find_all(pool: &PgPool, year: i32) -> ApiResult<GameVec> {
+    pub async fn find_all(connection: &mut PgConnection, year: i32) -> ApiResult<GameVec> {
         let games = sqlx::query_as!(
             Game,
-            r#"SELECT id, trophy_id, name, kind as "kind: GameKind", year FROM games WHERE year = $1 ORDER BY id"#, year
+            r#"SELECT id, trophy_id, name, kind as "kind: GameKind", scoring as "scoring: ScoringKind", attempts, aggregation as "aggregation: AttemptAggregation", closed, year FROM games WHERE year = $1 ORDER BY id"#, year
         )
-        .fetch_all(pool)
+        .fetch_all(connection)
         .await?;
 
         Ok(GameVec(games))
     }
 
-    /// Find all pending [Game]s.
-    pub async fn find_all_pending(year: i32, pool: &PgPool) -> ApiResult<Amount> {
+    /// Find all pending [Game]s, which are all games that haven't been closed yet.
+    pub async fn find_all_pending(year: i32, connection: &mut PgConnection) -> ApiResult<Amount> {
+        let amount = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) FROM games WHERE NOT closed AND year = $1"#,
+            year
+        )
+        .fetch_one(connection)
+        .await?
+        .unwrap_or(0);
+
+        Ok(Amount(amount))
+    }
+
+    /// Find all [Game]s that have outcomes without point values.
+    pub async fn find_all_unevaluated(
+        year: i32,
+        connection: &mut PgConnection,
+    ) -> ApiResult<Amount> {
         let amount = sqlx::query_scalar!(
             r#"SELECT COUNT(*) FROM (
                 SELECT DISTINCT game_id FROM game_team
                     INNER JOIN games ON game_team.game_id=games.id
-                    INNER JOIN teams ON game_team.team_id=teams.id
-                WHERE data IS NULL AND games.year = $1) AS temp"#,
+                WHERE point_value IS NULL AND games.year = $1) AS temp"#,
             year
         )
-        .fetch_one(pool)
+        .fetch_one(connection)
         .await?
         .unwrap_or(0);
 
         Ok(Amount(amount))
     }
+
     /// Try to get the [Game] of the specified ID.
     pub async fn find(id: Uuid, pool: &PgPool) -> ApiResult<Game> {
         let game = sqlx::query_as!(
             Game,
-            r#"SELECT id, trophy_id, name, kind as "kind: GameKind", year FROM games WHERE id = $1"#, id
+            r#"SELECT id, trophy_id, name, kind as "kind: GameKind", scoring as "scoring: ScoringKind", attempts, aggregation as "aggregation: AttemptAggregation", closed, year FROM games WHERE id = $1"#, id
         )
         .fetch_optional(pool)
         .await?;
@@ -90,16 +195,20 @@ impl Game {
 
     /// Create a new [Game].
     pub async fn create(create_game: CreateGame, pool: &PgPool) -> ApiResult<Game> {
+        create_game.validate()?;
         let mut tx = pool.begin().await?;
         let game: Game = sqlx::query_as!(
             Game,
-            r#"INSERT INTO games (id, trophy_id, name, kind, year)
-                VALUES ($1, $2, $3, $4, $5)
-                RETURNING id, trophy_id, name, kind as "kind: GameKind", year"#,
+            r#"INSERT INTO games (id, trophy_id, name, kind, scoring, attempts, aggregation, year)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                RETURNING id, trophy_id, name, kind as "kind: GameKind", scoring as "scoring: ScoringKind", attempts, aggregation as "aggregation: AttemptAggregation", closed, year"#,
             Uuid::now_v7(),
             create_game.trophy_id,
             create_game.name,
             create_game.kind as GameKind,
+            create_game.scoring as ScoringKind,
+            create_game.attempts,
+            create_game.aggregation as AttemptAggregation,
             create_game.year
         )
         .fetch_one(&mut *tx)
@@ -117,14 +226,23 @@ impl Game {
     /// Update the specified [Game].
     pub async fn update(id: Uuid, altered_game: CreateGame, pool: &PgPool) -> ApiResult<Game> {
         // NOTE I've decided against being able to change the year of already created games (for now)
+        altered_game.validate()?;
         let mut tx = pool.begin().await?;
         let game = sqlx::query_as!(
             Game,
-            r#"UPDATE games SET trophy_id = $1, name = $2, kind = $3 WHERE id = $4 RETURNING id, trophy_id, name, kind as "kind: GameKind", year"#,
-            altered_game.trophy_id, altered_game.name, altered_game.kind as GameKind, id
+            r#"UPDATE games SET trophy_id = $1, name = $2, kind = $3, scoring = $4, attempts = $5, aggregation = $6 WHERE id = $7 RETURNING id, trophy_id, name, kind as "kind: GameKind", scoring as "scoring: ScoringKind", attempts, aggregation as "aggregation: AttemptAggregation", closed, year"#,
+            altered_game.trophy_id,
+            altered_game.name,
+            altered_game.kind as GameKind,
+            altered_game.scoring as ScoringKind,
+            altered_game.attempts,
+            altered_game.aggregation as AttemptAggregation,
+            id
         )
         .fetch_one(&mut *tx)
         .await?;
+        // data that has already been entered has to fit the (possibly changed) kind and attempts
+        Outcome::store_values_for_game(&game, &mut tx).await?;
 
         tx.commit().await?;
         Ok(game)
@@ -135,7 +253,7 @@ impl Game {
         let mut tx = pool.begin().await?;
         let game = sqlx::query_as!(
             Game,
-            r#"DELETE FROM games WHERE id = $1 RETURNING id, trophy_id, name, kind as "kind: GameKind", year"#,
+            r#"DELETE FROM games WHERE id = $1 RETURNING id, trophy_id, name, kind as "kind: GameKind", scoring as "scoring: ScoringKind", attempts, aggregation as "aggregation: AttemptAggregation", closed, year"#,
             id
         )
         .fetch_one(&mut *tx)
@@ -145,20 +263,87 @@ impl Game {
         Ok(game)
     }
 
-    pub async fn is_pending(&self, pool: &PgPool) -> ApiResult<bool> {
+    /// Close or reopen the specified [Game].
+    /// A game can only be closed once all of its outcomes are in.
+    pub async fn set_closed(id: Uuid, closed: bool, pool: &PgPool) -> ApiResult<Game> {
+        let mut tx = pool.begin().await?;
+        let game = sqlx::query_as!(
+            Game,
+            r#"UPDATE games SET closed = $1 WHERE id = $2 RETURNING id, trophy_id, name, kind as "kind: GameKind", scoring as "scoring: ScoringKind", attempts, aggregation as "aggregation: AttemptAggregation", closed, year"#,
+            closed,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(CustomError::NotFoundError {
+            message: format!("Game {} could not be found.", id),
+        })?;
+
+        if game.closed && game.is_pending(&mut tx).await? {
+            return Err(CustomError::ValidationError {
+                message: format!(
+                    "Game {} can't be closed while outcomes are still missing.",
+                    game.name
+                ),
+            });
+        }
+
+        tx.commit().await?;
+        Ok(game)
+    }
+
+    /// Find the [Game] to write its outcomes and keep it from being closed or reopened until the caller's transaction ends.
+    /// Referees can't write once it has been closed, which fails with a [CustomError::GameClosedError].
+    /// Like [Outcome::create], this runs as part of the caller's transaction.
+    pub async fn find_for_writing(
+        id: Uuid,
+        role: &UserRole,
+        connection: &mut PgConnection,
+    ) -> ApiResult<Game> {
+        // a shared lock, so writes to the same game don't wait for each other but closing it does
+        let game = sqlx::query_as!(
+            Game,
+            r#"SELECT id, trophy_id, name, kind as "kind: GameKind", scoring as "scoring: ScoringKind", attempts, aggregation as "aggregation: AttemptAggregation", closed, year FROM games WHERE id = $1 FOR SHARE"#, id
+        )
+        .fetch_optional(connection)
+        .await?
+        .ok_or(CustomError::NotFoundError {
+            message: format!("Game {} could not be found.", id),
+        })?;
+
+        if *role == UserRole::Referee {
+            game.ensure_open()?;
+        }
+
+        Ok(game)
+    }
+
+    /// Fails with a [CustomError::GameClosedError] if this [Game] has been closed.
+    pub fn ensure_open(&self) -> ApiResult<()> {
+        if self.closed {
+            return Err(CustomError::GameClosedError {
+                message: format!("Game {} has to be reopened by an admin first.", self.name),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether any outcome of this [Game] is still missing its data.
+    pub async fn is_pending(&self, connection: &mut PgConnection) -> ApiResult<bool> {
         let amount = sqlx::query_scalar!(
             r#"SELECT COUNT(*) FROM (
                 SELECT DISTINCT game_id FROM game_team
                     INNER JOIN games ON game_team.game_id=games.id
                     INNER JOIN teams ON game_team.team_id=teams.id
-                WHERE data IS NULL
+                WHERE data IS NULL AND status = 'ok'
                 AND games.year = $1
                 AND games.id = $2)
             AS temp"#,
             self.year,
             self.id
         )
-        .fetch_one(pool)
+        .fetch_one(connection)
         .await?
         .unwrap_or(0);
 
@@ -170,8 +355,15 @@ impl fmt::Display for Game {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Game(id: {}, trophy_id: {}, name: {}, kind: {})",
-            self.id, self.trophy_id, self.name, self.kind
+            "Game(id: {}, trophy_id: {}, name: {}, kind: {}, scoring: {}, attempts: {}, aggregation: {}, closed: {})",
+            self.id,
+            self.trophy_id,
+            self.name,
+            self.kind,
+            self.scoring,
+            self.attempts,
+            self.aggregation,
+            self.closed
         )
     }
 }
@@ -18,6 +18,8 @@ pub enum SubjectType {
     User,
     Eval,
     General,
+    Category,
+    Adjustment,
 }
 
 impl Display for SubjectType {
@@ -30,6 +32,8 @@ impl Display for SubjectType {
             SubjectType::User => write!(f, "user"),
             SubjectType::Eval => write!(f, "eval"),
             SubjectType::General => write!(f, "general"),
+            SubjectType::Category => write!(f, "category"),
+            SubjectType::Adjustment => write!(f, "adjustment"),
         }
     }
 }
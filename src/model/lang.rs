@@ -0,0 +1,168 @@
+use serde::Deserialize;
+use std::fmt;
+
+use super::{GameKind, OutcomeStatus, TeamGender};
+
+/// The language of the labels in exported workbooks and PDFs.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    #[default]
+    De,
+    En,
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lang::De => write!(f, "De"),
+            Lang::En => write!(f, "En"),
+        }
+    }
+}
+
+/// The optional `lang` query parameter of the exports, German if omitted.
+#[derive(Deserialize)]
+pub struct LangQuery {
+    #[serde(default)]
+    lang: Lang,
+}
+
+impl std::ops::Deref for LangQuery {
+    type Target = Lang;
+
+    fn deref(&self) -> &Self::Target {
+        &self.lang
+    }
+}
+
+/// Headings, sheet names and texts of the exports in a single language.
+pub struct Labels {
+    pub place: &'static str,
+    pub trophy_id: &'static str,
+    pub team: &'static str,
+    pub category: &'static str,
+    pub adjustments: &'static str,
+    pub points: &'static str,
+    pub value: &'static str,
+    pub penalty: &'static str,
+    /// The name of the sheet with the point values of all teams in all games.
+    pub overview: &'static str,
+    pub results: &'static str,
+    pub certificates: &'static str,
+    pub provisional: &'static str,
+    /// Used for years without a configured [TrophyConfig::certificate_template](super::TrophyConfig::certificate_template).
+    pub certificate_template: &'static str,
+}
+
+const GERMAN: Labels = Labels {
+    place: "Platz",
+    trophy_id: "Nr.",
+    team: "Team",
+    category: "Kategorie",
+    adjustments: "Anpassungen",
+    points: "Punkte",
+    value: "Wert",
+    penalty: "Strafe",
+    overview: "Übersicht",
+    results: "Ergebnisse",
+    certificates: "Urkunden",
+    provisional: "vorläufig",
+    certificate_template: "Urkunde
+{team}
+belegt in der Kategorie {category}
+mit {points} Punkten den
+{place}. Platz
+bei der Klostertrophy {year}",
+};
+
+const ENGLISH: Labels = Labels {
+    place: "Place",
+    trophy_id: "No.",
+    team: "Team",
+    category: "Category",
+    adjustments: "Adjustments",
+    points: "Points",
+    value: "Value",
+    penalty: "Penalty",
+    overview: "Overview",
+    results: "Results",
+    certificates: "Certificates",
+    provisional: "provisional",
+    certificate_template: "Certificate
+{team}
+finished the Klostertrophy {year}
+in the category {category}
+with {points} points in
+place {place}",
+};
+
+impl Lang {
+    pub fn labels(self) -> &'static Labels {
+        match self {
+            Lang::De => &GERMAN,
+            Lang::En => &ENGLISH,
+        }
+    }
+
+    pub fn game_kind(self, kind: &GameKind) -> &'static str {
+        match (self, kind) {
+            (Lang::De, GameKind::Points) => "Punkte",
+            (Lang::De, GameKind::Time) => "Zeit",
+            (Lang::De, GameKind::Distance) => "Weite",
+            (Lang::De, GameKind::LowestPoints) => "Wenigste Punkte",
+            (Lang::De, GameKind::HitsWithTime) => "Treffer mit Zeit",
+            (Lang::En, GameKind::Points) => "Points",
+            (Lang::En, GameKind::Time) => "Time",
+            (Lang::En, GameKind::Distance) => "Distance",
+            (Lang::En, GameKind::LowestPoints) => "Lowest points",
+            (Lang::En, GameKind::HitsWithTime) => "Hits with time",
+        }
+    }
+
+    pub fn status(self, status: OutcomeStatus) -> &'static str {
+        match (self, status) {
+            (Lang::De, OutcomeStatus::Ok) => "Gewertet",
+            (Lang::De, OutcomeStatus::Dns) => "Nicht angetreten",
+            (Lang::De, OutcomeStatus::Dnf) => "Nicht beendet",
+            (Lang::De, OutcomeStatus::Dsq) => "Disqualifiziert",
+            (Lang::En, OutcomeStatus::Ok) => "Ranked",
+            (Lang::En, OutcomeStatus::Dns) => "Did not start",
+            (Lang::En, OutcomeStatus::Dnf) => "Did not finish",
+            (Lang::En, OutcomeStatus::Dsq) => "Disqualified",
+        }
+    }
+
+    pub fn gender(self, gender: TeamGender) -> &'static str {
+        match (self, gender) {
+            (Lang::De, TeamGender::Female) => "Damen",
+            (Lang::De, TeamGender::Male) => "Herren",
+            (Lang::De, TeamGender::Mixed) => "Mixed",
+            (Lang::En, TeamGender::Female) => "Women",
+            (Lang::En, TeamGender::Male) => "Men",
+            (Lang::En, TeamGender::Mixed) => "Mixed",
+        }
+    }
+
+    /// Translate the names of the categories that are created for each gender, all other names are chosen by the organisers.
+    pub fn category(self, name: &str) -> String {
+        [TeamGender::Female, TeamGender::Male, TeamGender::Mixed]
+            .into_iter()
+            .find(|gender| gender.to_string() == name)
+            .map(|gender| self.gender(gender).to_string())
+            .unwrap_or(name.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_gender_categories_are_translated() {
+        assert_eq!(Lang::De.category("Female"), "Damen");
+        assert_eq!(Lang::En.category("Male"), "Men");
+        assert_eq!(Lang::De.category("Senioren"), "Senioren");
+        assert_eq!(Lang::En.category("female"), "female");
+    }
+}
@@ -54,6 +54,9 @@ pub struct ImportMetadata {
     pub trophy_id_header: String,
     pub name_header: String,
     pub gender_header: String,
+    /// Teams are assigned to the category named after their gender if this is omitted.
+    #[serde(default)]
+    pub category_header: Option<String>,
     pub year: i32,
 }
 
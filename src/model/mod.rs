@@ -1,19 +1,29 @@
+mod attempt;
+mod category;
+mod config;
 mod error;
 mod game;
 mod history;
+mod lang;
 mod misc;
 mod outcome;
 mod parsed_outcome;
+mod point_adjustment;
 mod team;
 mod user;
 mod user_token;
 
+pub use attempt::*;
+pub use category::*;
+pub use config::*;
 pub use error::*;
 pub use game::*;
 pub use history::*;
+pub use lang::*;
 pub use misc::*;
 pub use outcome::*;
 pub use parsed_outcome::*;
+pub use point_adjustment::*;
 use serde::Serialize;
 use std::fmt::Display;
 pub use team::*;
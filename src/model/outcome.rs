@@ -1,17 +1,48 @@
-use super::{Game, GameKind, ParsedOutcome, TeamGender, TypeInfo};
+use super::{Attempt, Game, GameKind, ParsedOutcome, StoredValue, TeamGender, TypeInfo, Value};
 use crate::{
     ApiResult,
-    model::{CustomError, Team},
+    model::{CustomError, RowError, Team, UserRole},
 };
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, PgConnection, PgPool};
-use std::fmt::{self, Display};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+};
 use uuid::Uuid;
 
+/// Whether a team has played a game - only [OutcomeStatus::Ok] needs data.
+#[derive(Deserialize, Serialize, sqlx::Type, Clone, Copy, PartialEq, Debug, Default)]
+#[sqlx(type_name = "outcome_status")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum OutcomeStatus {
+    #[default]
+    Ok,
+    /// Did not start.
+    Dns,
+    /// Did not finish.
+    Dnf,
+    /// Disqualified.
+    Dsq,
+}
+
+impl fmt::Display for OutcomeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutcomeStatus::Ok => write!(f, "Ok"),
+            OutcomeStatus::Dns => write!(f, "Dns"),
+            OutcomeStatus::Dnf => write!(f, "Dnf"),
+            OutcomeStatus::Dsq => write!(f, "Dsq"),
+        }
+    }
+}
+
 /// This module provides all routes concerning outcomes.
 /// As the name "Result" was already taken for the programming-structure, I'm using "outcome".
 /// NOTE that sqlx wants results of joins to be Optional<T> - which is the case for `game_name` and `team_name` here.
-#[derive(Deserialize, Serialize, FromRow)]
+/// Only ever sent, changes are received as [UpdateOutcome] or [SetOutcome], so clients can't alter e.g. names or point values.
+#[derive(Serialize, FromRow)]
 #[sqlx(type_name = "game_team")]
 #[sqlx(rename_all = "lowercase")]
 pub struct Outcome {
@@ -23,22 +54,65 @@ pub struct Outcome {
     pub team_trophy_id: i32,
     pub team_name: String,
     pub team_gender: TeamGender,
+    /// The data in its normalised form, the typed value is stored alongside it by [Outcome::set_data].
     pub data: Option<String>,
+    pub value_integer: Option<i32>,
+    pub value_millis: Option<i64>,
+    pub value_millimetres: Option<i64>,
+    pub status: OutcomeStatus,
+    /// Deducted from points and hits, added to lowest points.
+    pub penalty_points: Option<i32>,
+    /// Added to times.
+    pub penalty_millis: Option<i64>,
     pub point_value: Option<i32>,
+    /// Incremented on every change of the data, status or penalties.
+    /// Updates must send the version they are based on (or an `If-Match`-header), so they don't silently overwrite each other.
+    pub version: i32,
+    /// Set if an admin has overridden the point value, evaluations keep such values until the data changes.
+    pub override_reason: Option<String>,
 }
 #[derive(Serialize)]
 pub struct OutcomeVec(pub Vec<Outcome>);
 
-pub struct GenderOutcomes {
-    pub female_outcomes: Vec<ParsedOutcome>,
-    pub male_outcomes: Vec<ParsedOutcome>,
+/// The part of an [Outcome] that referees enter, used to submit all outcomes of a game at once.
+#[derive(Deserialize)]
+pub struct SetOutcome {
+    pub team_id: Uuid,
+    pub data: Option<String>,
+    /// Falls back to [OutcomeStatus::Ok] so existing clients keep working.
+    #[serde(default)]
+    pub status: OutcomeStatus,
+    pub penalty_points: Option<i32>,
+    pub penalty_millis: Option<i64>,
+    /// The version the update is based on, always required but a single [UpdateOutcome] may send it as `If-Match`-header instead.
+    pub version: Option<i32>,
 }
 
+/// The payload of `PUT /outcomes`, any other field of the [Outcome] (like its point value) is ignored.
+#[derive(Deserialize)]
+pub struct UpdateOutcome {
+    pub game_id: Uuid,
+    #[serde(flatten)]
+    pub outcome: SetOutcome,
+}
+
+/// Manually sets the point value of a team's outcome, e.g. after a protest.
+#[derive(Deserialize)]
+pub struct OverridePointValue {
+    pub team_id: Uuid,
+    pub point_value: i32,
+    /// Why the point value has been changed, e.g. the decision on a protest.
+    pub reason: String,
+}
+
+/// All [ParsedOutcome]s of a game, grouped by the [Category](super::Category) of their team since each category is ranked on its own.
+pub struct CategoryOutcomes(pub HashMap<Uuid, Vec<ParsedOutcome>>);
+
 impl Outcome {
     pub async fn find_all(pool: &PgPool) -> ApiResult<OutcomeVec> {
         let outcomes = sqlx::query_as!(
             Outcome,
-            r#"SELECT game_id, games.trophy_id as game_trophy_id, games.name as game_name, games.kind as "game_kind: GameKind", team_id, teams.trophy_id as team_trophy_id, teams.name as team_name, teams.gender as "team_gender: TeamGender", data, point_value FROM game_team
+            r#"SELECT game_id, games.trophy_id as game_trophy_id, games.name as game_name, games.kind as "game_kind: GameKind", team_id, teams.trophy_id as team_trophy_id, teams.name as team_name, teams.gender as "team_gender: TeamGender", data, value_integer, value_millis, value_millimetres, status as "status: OutcomeStatus", penalty_points, penalty_millis, point_value, version, override_reason FROM game_team
                 INNER JOIN games ON game_team.game_id=games.id
                 INNER JOIN teams ON game_team.team_id=teams.id
             ORDER BY game_id"#
@@ -49,27 +123,69 @@ impl Outcome {
         Ok(OutcomeVec(outcomes))
     }
 
+    /// Find all [Outcome]s of the specified year.
+    pub async fn find_all_for_year(year: i32, pool: &PgPool) -> ApiResult<OutcomeVec> {
+        let outcomes = sqlx::query_as!(
+            Outcome,
+            r#"SELECT game_id, games.trophy_id as game_trophy_id, games.name as game_name, games.kind as "game_kind: GameKind", team_id, teams.trophy_id as team_trophy_id, teams.name as team_name, teams.gender as "team_gender: TeamGender", data, value_integer, value_millis, value_millimetres, status as "status: OutcomeStatus", penalty_points, penalty_millis, point_value, version, override_reason FROM game_team
+                INNER JOIN games ON game_team.game_id=games.id
+                INNER JOIN teams ON game_team.team_id=teams.id
+            WHERE games.year = $1 ORDER BY game_id"#,
+            year
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(OutcomeVec(outcomes))
+    }
+
     /// Find all [Outcome]s for the specified [Game].
-    pub async fn find_all_for_game(game_id: Uuid, pool: &PgPool) -> ApiResult<OutcomeVec> {
+    pub async fn find_all_for_game(
+        game_id: Uuid,
+        connection: &mut PgConnection,
+    ) -> ApiResult<OutcomeVec> {
         let outcomes = sqlx::query_as!(
             Outcome,
-            r#"SELECT game_id, games.trophy_id as game_trophy_id, games.name as game_name, games.kind as "game_kind: GameKind", team_id, teams.trophy_id as team_trophy_id, teams.name as team_name, teams.gender as "team_gender: TeamGender", data, point_value FROM game_team
+            r#"SELECT game_id, games.trophy_id as game_trophy_id, games.name as game_name, games.kind as "game_kind: GameKind", team_id, teams.trophy_id as team_trophy_id, teams.name as team_name, teams.gender as "team_gender: TeamGender", data, value_integer, value_millis, value_millimetres, status as "status: OutcomeStatus", penalty_points, penalty_millis, point_value, version, override_reason FROM game_team
                 INNER JOIN games ON game_team.game_id=games.id
                 INNER JOIN teams ON game_team.team_id=teams.id
             WHERE game_id = $1 ORDER BY game_id"#,
             game_id
         )
-        .fetch_all(pool)
+        .fetch_all(connection)
         .await?;
 
         Ok(OutcomeVec(outcomes))
     }
 
+    /// Find the [Outcome] of the specified [Team] in the specified [Game].
+    pub async fn find(
+        game_id: Uuid,
+        team_id: Uuid,
+        connection: &mut PgConnection,
+    ) -> ApiResult<Outcome> {
+        let outcome = sqlx::query_as!(
+            Outcome,
+            r#"SELECT game_id, games.trophy_id as game_trophy_id, games.name as game_name, games.kind as "game_kind: GameKind", team_id, teams.trophy_id as team_trophy_id, teams.name as team_name, teams.gender as "team_gender: TeamGender", data, value_integer, value_millis, value_millimetres, status as "status: OutcomeStatus", penalty_points, penalty_millis, point_value, version, override_reason FROM game_team
+                INNER JOIN games ON game_team.game_id=games.id
+                INNER JOIN teams ON game_team.team_id=teams.id
+            WHERE game_id = $1 AND team_id = $2"#,
+            game_id,
+            team_id
+        )
+        .fetch_optional(connection)
+        .await?;
+
+        outcome.ok_or(CustomError::NotFoundError {
+            message: format!("Team {} has no outcome for game {}.", team_id, game_id),
+        })
+    }
+
     /// Find all [Outcome]s for the specified [Team].
     pub async fn find_all_for_team(team_id: Uuid, pool: &PgPool) -> ApiResult<OutcomeVec> {
         let outcomes = sqlx::query_as!(
             Outcome,
-            r#"SELECT game_id, games.trophy_id as game_trophy_id, games.name as game_name, games.kind as "game_kind: GameKind", team_id, teams.trophy_id as team_trophy_id, teams.name as team_name, teams.gender as "team_gender: TeamGender", data, point_value FROM game_team
+            r#"SELECT game_id, games.trophy_id as game_trophy_id, games.name as game_name, games.kind as "game_kind: GameKind", team_id, teams.trophy_id as team_trophy_id, teams.name as team_name, teams.gender as "team_gender: TeamGender", data, value_integer, value_millis, value_millimetres, status as "status: OutcomeStatus", penalty_points, penalty_millis, point_value, version, override_reason FROM game_team
                 INNER JOIN games ON game_team.game_id=games.id
                 INNER JOIN teams ON game_team.team_id=teams.id
             WHERE team_id = $1 ORDER BY game_id"#,
@@ -94,7 +210,7 @@ impl Outcome {
         let outcome = sqlx::query_as!(
             Outcome,
             r#"WITH inserted AS (INSERT INTO game_team (game_id, team_id) VALUES ($1, $2) RETURNING *)
-            SELECT game_id, games.trophy_id as game_trophy_id, games.name as game_name, games.kind as "game_kind: GameKind", team_id, teams.trophy_id as team_trophy_id, teams.name as team_name, teams.gender as "team_gender: TeamGender", data, point_value
+            SELECT game_id, games.trophy_id as game_trophy_id, games.name as game_name, games.kind as "game_kind: GameKind", team_id, teams.trophy_id as team_trophy_id, teams.name as team_name, teams.gender as "team_gender: TeamGender", data, value_integer, value_millis, value_millimetres, status as "status: OutcomeStatus", penalty_points, penalty_millis, point_value, version, override_reason
                 FROM inserted
                 INNER JOIN games ON inserted.game_id=games.id
                 INNER JOIN teams ON inserted.team_id=teams.id"#,
@@ -106,71 +222,377 @@ impl Outcome {
         Ok(outcome)
     }
 
-    /// Set the data of this [Outcome].
-    pub async fn set_data(&self, pool: &PgPool) -> ApiResult<Outcome> {
+    /// Set the data, status and penalties of an [Outcome] after validating them against the kind of its [Game].
+    /// The data is stored in its normalised form (blank data resets the outcome), its typed value is stored alongside.
+    /// Games with several attempts ignore the sent data, their outcomes always hold the aggregate of their [Attempt]s.
+    /// Fails with a [CustomError::ConflictError] containing the current [Outcome] if its version differs from the sent one,
+    /// and for referees with a [CustomError::GameClosedError] once the game has been closed.
+    pub async fn set_data(
+        update: UpdateOutcome,
+        role: &UserRole,
+        pool: &PgPool,
+    ) -> ApiResult<Outcome> {
+        if update.outcome.version.is_none() {
+            return Err(CustomError::ValidationError {
+                message: "The version of the outcome is missing, send it in the body or as If-Match-header.".to_string(),
+            });
+        }
         let mut tx = pool.begin().await?;
+        let game = Game::find_for_writing(update.game_id, role, &mut tx).await?;
+        let outcome = Outcome::write(&game, &update.outcome, &mut tx).await?;
+        tx.commit().await?;
+
+        Ok(outcome)
+    }
+
+    /// Set the data of several [Outcome]s of the [Game] at once, see [Outcome::set_data].
+    /// Either all of them are stored or none, in which case a [CustomError::BulkValidationError] lists the error of every failed row.
+    pub async fn set_data_for_game(
+        game_id: Uuid,
+        outcomes: Vec<SetOutcome>,
+        role: &UserRole,
+        pool: &PgPool,
+    ) -> ApiResult<OutcomeVec> {
+        let mut tx = pool.begin().await?;
+        let game = &Game::find_for_writing(game_id, role, &mut tx).await?;
+        let mut updated = Vec::<Outcome>::new();
+        let mut errors = Vec::<RowError>::new();
+        let mut sent = HashSet::<Uuid>::new();
+        for outcome in outcomes {
+            if !sent.insert(outcome.team_id) {
+                errors.push(RowError {
+                    team_id: outcome.team_id,
+                    error: "The team has been sent more than once.".to_string(),
+                });
+                continue;
+            }
+            if outcome.version.is_none() {
+                errors.push(RowError {
+                    team_id: outcome.team_id,
+                    error: "The version of the outcome is missing.".to_string(),
+                });
+                continue;
+            }
+
+            // only errors concerning the row itself are collected, anything else aborts right away
+            match Outcome::write(game, &outcome, &mut tx).await {
+                Ok(outcome) => updated.push(outcome),
+                Err(
+                    err @ (CustomError::ValidationError { .. }
+                    | CustomError::ConflictError { .. }
+                    | CustomError::NotFoundError { .. }),
+                ) => errors.push(RowError {
+                    team_id: outcome.team_id,
+                    error: err.to_string(),
+                }),
+                Err(err) => return Err(err),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(CustomError::BulkValidationError {
+                message: format!(
+                    "{} of the sent outcomes for game {} are invalid, none have been stored.",
+                    errors.len(),
+                    game.name
+                ),
+                rows: errors,
+            });
+        }
+
+        tx.commit().await?;
+        Ok(OutcomeVec(updated))
+    }
+
+    /// Validate and store the [SetOutcome] for the team in the [Game].
+    /// The callers make sure the version has been sent, an update without one is always rejected as conflict.
+    /// Like [Outcome::create], this runs as part of the caller's transaction.
+    async fn write(
+        game: &Game,
+        outcome: &SetOutcome,
+        connection: &mut PgConnection,
+    ) -> ApiResult<Outcome> {
+        Value::validate_penalty(&game.kind, outcome.penalty_points, outcome.penalty_millis)?;
+
+        let value = match outcome.data.as_deref().map(str::trim) {
+            _ if game.attempts > 1 => {
+                Attempt::aggregate(game, outcome.team_id, &mut *connection).await?
+            }
+            Some(data) if !data.is_empty() => Some(Value::parse(data, &game.kind)?),
+            _ => None,
+        };
+        // the data is stored in its normalised form, so e.g. times always look the same
+        let data = value.as_ref().map(Value::to_string);
+        let stored = value.as_ref().map(StoredValue::from).unwrap_or_default();
+
+        let updated = sqlx::query_as!(
+                Outcome,
+                r#"WITH updated AS (UPDATE game_team SET data = $1, value_integer = $2, value_millis = $3, value_millimetres = $4, status = $5, penalty_points = $6, penalty_millis = $7, override_reason = NULL, version = version + 1 WHERE game_id = $8 AND team_id = $9 AND version = $10 RETURNING *)
+                SELECT game_id, games.trophy_id as game_trophy_id, games.name as game_name, games.kind as "game_kind: GameKind", team_id, teams.trophy_id as team_trophy_id, teams.name as team_name, teams.gender as "team_gender: TeamGender", data, value_integer, value_millis, value_millimetres, status as "status: OutcomeStatus", penalty_points, penalty_millis, point_value, version, override_reason FROM updated
+                    INNER JOIN games ON updated.game_id=games.id
+                    INNER JOIN teams ON updated.team_id=teams.id"#,
+                data,
+                stored.integer,
+                stored.millis,
+                stored.millimetres,
+                outcome.status as OutcomeStatus,
+                outcome.penalty_points,
+                outcome.penalty_millis,
+                game.id,
+                outcome.team_id,
+                outcome.version
+            )
+            .fetch_optional(&mut *connection)
+            .await?;
+
+        match updated {
+            Some(updated) => Ok(updated),
+            None => {
+                Err(Outcome::conflict(game.id, outcome.team_id, outcome.version, connection).await)
+            }
+        }
+    }
+
+    /// The error for an update that didn't match the version of the outcome, it carries the current outcome.
+    /// Fails with a [CustomError::NotFoundError] instead if the outcome doesn't exist at all.
+    async fn conflict(
+        game_id: Uuid,
+        team_id: Uuid,
+        version: Option<i32>,
+        connection: &mut PgConnection,
+    ) -> CustomError {
+        let current = match Outcome::find(game_id, team_id, connection).await {
+            Ok(current) => current,
+            Err(err) => return err,
+        };
+        let message = match version {
+            Some(version) => format!(
+                "The outcome is at version {}, but the update was based on version {}.",
+                current.version, version
+            ),
+            None => format!(
+                "The outcome is at version {}, but the update didn't name the version it was based on.",
+                current.version
+            ),
+        };
+        match serde_json::to_value(current) {
+            Ok(current) => CustomError::ConflictError { message, current },
+            Err(err) => CustomError::ParseError {
+                message: err.to_string(),
+            },
+        }
+    }
+
+    /// Parse the data of all [Outcome]s of the [Game] again and store their normalised data and typed values, e.g. after its kind has changed.
+    /// Fails with a [CustomError::ValidationError] as soon as any data doesn't fit the kind.
+    /// Like [Outcome::create], this runs as part of the caller's transaction.
+    pub async fn store_values_for_game(
+        game: &Game,
+        connection: &mut PgConnection,
+    ) -> ApiResult<()> {
+        Attempt::fit_to_game(game, connection).await?;
+
+        for outcome in Outcome::find_all_for_game(game.id, connection).await?.0 {
+            let value = match outcome.data.as_deref() {
+                _ if game.attempts > 1 => {
+                    Attempt::aggregate(game, outcome.team_id, connection).await?
+                }
+                Some(data) => match Value::parse(data, &game.kind) {
+                    Ok(value) => Some(value),
+                    Err(CustomError::ValidationError { message }) => {
+                        return Err(CustomError::ValidationError {
+                            message: format!(
+                                "The data of team {} doesn't fit game {} anymore: {}",
+                                outcome.team_name, game.name, message
+                            ),
+                        });
+                    }
+                    Err(err) => return Err(err),
+                },
+                None => None,
+            };
+            Value::validate_penalty(&game.kind, outcome.penalty_points, outcome.penalty_millis)
+                .map_err(|err| match err {
+                    CustomError::ValidationError { message } => CustomError::ValidationError {
+                        message: format!(
+                            "The penalty of team {} doesn't fit game {} anymore: {}",
+                            outcome.team_name, game.name, message
+                        ),
+                    },
+                    err => err,
+                })?;
+            Outcome::store_value(
+                outcome.game_id,
+                outcome.team_id,
+                value,
+                outcome.version,
+                connection,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Store the value as normalised data and typed value, `None` resets the data.
+    /// Fails with a [CustomError::ConflictError] containing the current [Outcome] if it isn't at the version anymore.
+    /// Like [Outcome::create], this runs as part of the caller's transaction.
+    pub async fn store_value(
+        game_id: Uuid,
+        team_id: Uuid,
+        value: Option<Value>,
+        version: i32,
+        connection: &mut PgConnection,
+    ) -> ApiResult<Outcome> {
+        let data = value.as_ref().map(Value::to_string);
+        let stored = value.as_ref().map(StoredValue::from).unwrap_or_default();
+
         let outcome = sqlx::query_as!(
                 Outcome,
-                r#"WITH updated AS (UPDATE game_team SET data = $1 WHERE game_id = $2 AND team_id = $3 RETURNING *)
-                SELECT game_id, games.trophy_id as game_trophy_id, games.name as game_name, games.kind as "game_kind: GameKind", team_id, teams.trophy_id as team_trophy_id, teams.name as team_name, teams.gender as "team_gender: TeamGender", data, point_value FROM updated
+                r#"WITH updated AS (UPDATE game_team SET data = $1, value_integer = $2, value_millis = $3, value_millimetres = $4, override_reason = NULL, version = version + 1 WHERE game_id = $5 AND team_id = $6 AND version = $7 RETURNING *)
+                SELECT game_id, games.trophy_id as game_trophy_id, games.name as game_name, games.kind as "game_kind: GameKind", team_id, teams.trophy_id as team_trophy_id, teams.name as team_name, teams.gender as "team_gender: TeamGender", data, value_integer, value_millis, value_millimetres, status as "status: OutcomeStatus", penalty_points, penalty_millis, point_value, version, override_reason FROM updated
                     INNER JOIN games ON updated.game_id=games.id
                     INNER JOIN teams ON updated.team_id=teams.id"#,
-                self.data, self.game_id, self.team_id
+                data, stored.integer, stored.millis, stored.millimetres, game_id, team_id, version
             )
-            .fetch_one(&mut *tx)
+            .fetch_optional(&mut *connection)
             .await?;
-        tx.commit().await?;
 
-        Ok(outcome)
+        match outcome {
+            Some(outcome) => Ok(outcome),
+            None => Err(Outcome::conflict(game_id, team_id, Some(version), connection).await),
+        }
     }
 
+    /// Persist the point value [evaluate](crate::eval) has assigned to the [ParsedOutcome].
+    /// Like [Outcome::create], this runs as part of the caller's transaction.
     pub async fn set_point_value(
         parsed_outcome: ParsedOutcome,
-        pool: &PgPool,
+        connection: &mut PgConnection,
     ) -> ApiResult<Outcome> {
-        let mut tx = pool.begin().await?;
         let outcome = sqlx::query_as!(
                 Outcome,
                 r#"WITH updated AS (UPDATE game_team SET point_value = $1 WHERE game_id = $2 AND team_id = $3 RETURNING *)
-                SELECT game_id, games.trophy_id as game_trophy_id, games.name as game_name, games.kind as "game_kind: GameKind", team_id, teams.trophy_id as team_trophy_id, teams.name as team_name, teams.gender as "team_gender: TeamGender", data, point_value FROM updated
+                SELECT game_id, games.trophy_id as game_trophy_id, games.name as game_name, games.kind as "game_kind: GameKind", team_id, teams.trophy_id as team_trophy_id, teams.name as team_name, teams.gender as "team_gender: TeamGender", data, value_integer, value_millis, value_millimetres, status as "status: OutcomeStatus", penalty_points, penalty_millis, point_value, version, override_reason FROM updated
                             INNER JOIN games ON updated.game_id=games.id
                             INNER JOIN teams ON updated.team_id=teams.id"#,
                 parsed_outcome.point_value, parsed_outcome.game_id, parsed_outcome.team.id
             )
+            .fetch_one(connection)
+            .await?;
+
+        Ok(outcome)
+    }
+
+    /// Manually set the point value of a team's [Outcome] in the [Game] and update the team's total accordingly.
+    /// Evaluations and recomputes keep the override until the data of the outcome changes.
+    /// Returns the point value before the override next to the updated [Outcome].
+    pub async fn override_point_value(
+        game_id: Uuid,
+        point_override: OverridePointValue,
+        pool: &PgPool,
+    ) -> ApiResult<(Option<i32>, Outcome)> {
+        if point_override.point_value < 0 {
+            return Err(CustomError::ValidationError {
+                message: format!(
+                    "A point value can't be negative, got {}.",
+                    point_override.point_value
+                ),
+            });
+        }
+        let reason = point_override.reason.trim();
+        if reason.is_empty() {
+            return Err(CustomError::ValidationError {
+                message: "The reason for overriding the point value is missing.".to_string(),
+            });
+        }
+        let game = Game::find(game_id, pool).await?;
+
+        let mut tx = pool.begin().await?;
+        let previous = Outcome::find(game.id, point_override.team_id, &mut tx)
+            .await?
+            .point_value;
+        let outcome = sqlx::query_as!(
+                Outcome,
+                r#"WITH updated AS (UPDATE game_team SET point_value = $1, override_reason = $2 WHERE game_id = $3 AND team_id = $4 RETURNING *)
+                SELECT game_id, games.trophy_id as game_trophy_id, games.name as game_name, games.kind as "game_kind: GameKind", team_id, teams.trophy_id as team_trophy_id, teams.name as team_name, teams.gender as "team_gender: TeamGender", data, value_integer, value_millis, value_millimetres, status as "status: OutcomeStatus", penalty_points, penalty_millis, point_value, version, override_reason FROM updated
+                    INNER JOIN games ON updated.game_id=games.id
+                    INNER JOIN teams ON updated.team_id=teams.id"#,
+                point_override.point_value, reason, game.id, point_override.team_id
+            )
             .fetch_one(&mut *tx)
             .await?;
+        Team::recompute_points(game.year, &mut tx).await?;
+
         tx.commit().await?;
+        Ok((previous, outcome))
+    }
 
-        Ok(outcome)
+    /// Remove the point values of all [Outcome]s of the specified year, so they can be assigned from scratch.
+    /// Like [Outcome::create], this runs as part of the caller's transaction.
+    pub async fn reset_point_values(year: i32, connection: &mut PgConnection) -> ApiResult<()> {
+        sqlx::query!(
+            r#"UPDATE game_team SET point_value = NULL FROM games WHERE game_team.game_id = games.id AND games.year = $1"#,
+            year
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(())
     }
 
-    /// Parse all outcomes for game and return as ParsedOutcome.
-    /// Note that this expects the game to be fully complete.
-    pub async fn parse_by_gender_for_game(game: &Game, pool: &PgPool) -> ApiResult<GenderOutcomes> {
-        let mut female_outcomes = Vec::<ParsedOutcome>::new();
-        let mut male_outcomes = Vec::<ParsedOutcome>::new();
-        // sort outcomes by gender
-        for outcome in Outcome::find_all_for_game(game.id, pool).await?.0 {
-            let data = outcome.data.clone();
-            // return early if we miss data
-            if data.is_none() {
-                return Err(CustomError::EarlyEvaluationError {
+    /// Parse every outcome of the game on its own and keep the result next to the outcome, so callers can decide
+    /// whether to stop at the first error or collect all of them.
+    /// Outcomes that still miss their data fail with [CustomError::EarlyEvaluationError].
+    pub async fn parse_each_for_game(
+        game: &Game,
+        connection: &mut PgConnection,
+    ) -> ApiResult<Vec<(Outcome, ApiResult<ParsedOutcome>)>> {
+        let mut teams: HashMap<Uuid, Team> = Team::find_all_for_game(game.id, connection)
+            .await?
+            .0
+            .into_iter()
+            .map(|team| (team.id, team))
+            .collect();
+        let mut parsed = Vec::<(Outcome, ApiResult<ParsedOutcome>)>::new();
+        for outcome in Outcome::find_all_for_game(game.id, connection).await?.0 {
+            // teams that didn't finish need no data
+            let result = if outcome.status == OutcomeStatus::Ok && outcome.data.is_none() {
+                Err(CustomError::EarlyEvaluationError {
                     message: format!("Tried parsing the outcome {}, but it had no data.", outcome),
-                });
-            }
+                })
+            } else {
+                // every team has a single outcome per game
+                let team = teams
+                    .remove(&outcome.team_id)
+                    .ok_or(CustomError::NotFoundError {
+                        message: format!("Team {} could not be found.", outcome.team_id),
+                    })?;
+                ParsedOutcome::from_outcome(&outcome, &game.kind, team)
+            };
+            parsed.push((outcome, result));
+        }
 
-            let team = Team::find(outcome.team_id, pool).await?;
-            let parsed_outcome = ParsedOutcome::from(data.unwrap(), &game.kind, game.id, team)?;
-            match parsed_outcome.team.gender {
-                TeamGender::Female => female_outcomes.push(parsed_outcome),
-                TeamGender::Male => male_outcomes.push(parsed_outcome),
-            }
+        Ok(parsed)
+    }
+
+    /// Parse all outcomes for game and return as ParsedOutcome.
+    /// Note that this expects the game to be fully complete.
+    pub async fn parse_by_category_for_game(
+        game: &Game,
+        connection: &mut PgConnection,
+    ) -> ApiResult<CategoryOutcomes> {
+        let mut outcomes = HashMap::<Uuid, Vec<ParsedOutcome>>::new();
+        // sort outcomes by category, the first outcome that can't be parsed fails the whole game
+        for (_, parsed_outcome) in Outcome::parse_each_for_game(game, connection).await? {
+            let parsed_outcome = parsed_outcome?;
+            outcomes
+                .entry(parsed_outcome.team.category_id)
+                .or_default()
+                .push(parsed_outcome);
         }
 
-        Ok(GenderOutcomes {
-            male_outcomes,
-            female_outcomes,
-        })
+        Ok(CategoryOutcomes(outcomes))
     }
 }
 
@@ -178,8 +600,8 @@ impl Display for Outcome {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Outcome(game_id: {}, team_id: {}, data: {:?})",
-            self.game_id, self.team_id, self.data
+            "Outcome(game_id: {}, team_id: {}, data: {:?}, status: {}, version: {})",
+            self.game_id, self.team_id, self.data, self.status, self.version
         )
     }
 }
@@ -205,3 +627,115 @@ impl TypeInfo for OutcomeVec {
         "OutcomeVec".to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db::TestYear;
+    use actix_web::{ResponseError, http::StatusCode};
+
+    #[test]
+    fn update_outcome_ignores_other_fields() {
+        let update: UpdateOutcome = serde_json::from_str(
+            r#"{
+                "game_id": "01a1492a-e365-7480-99a4-7ed85e33db63",
+                "team_id": "01a1492a-e3ea-70f1-8b63-0f933efb659e",
+                "team_name": "Team 1",
+                "data": "10",
+                "point_value": 50,
+                "version": 3
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(update.outcome.data.as_deref(), Some("10"));
+        assert_eq!(update.outcome.status, OutcomeStatus::Ok);
+        assert_eq!(update.outcome.version, Some(3));
+    }
+
+    /// Needs a database with the current schema at `DATABASE_URL`, run with `cargo test -- --ignored`.
+    #[actix_web::test]
+    #[ignore = "requires a database at DATABASE_URL"]
+    async fn rows_without_current_version_are_rejected() {
+        let test_year = TestYear::new().await;
+        let pool = &test_year.pool;
+
+        let game = test_year.game(test_year.create_game(1, "Kegeln")).await;
+        let mut teams = Vec::new();
+        for trophy_id in 1..=3 {
+            teams.push(test_year.team(trophy_id, TeamGender::Female).await.id);
+        }
+
+        let row = |team_id, version| SetOutcome {
+            team_id,
+            data: Some("10".to_string()),
+            status: OutcomeStatus::Ok,
+            penalty_points: None,
+            penalty_millis: None,
+            version,
+        };
+        let result = Outcome::set_data_for_game(
+            game.id,
+            vec![
+                row(teams[0], Some(0)),
+                row(teams[1], None),
+                row(teams[2], Some(5)),
+            ],
+            &UserRole::Admin,
+            pool,
+        )
+        .await;
+        let mut connection = pool.acquire().await.unwrap();
+        let stored = Outcome::find(game.id, teams[0], &mut connection)
+            .await
+            .unwrap();
+
+        drop(connection);
+        test_year.clean_up().await;
+
+        let Err(CustomError::BulkValidationError { rows, .. }) = result else {
+            panic!("the update should have been rejected");
+        };
+        let failed: Vec<Uuid> = rows.iter().map(|row| row.team_id).collect();
+        assert_eq!(failed, vec![teams[1], teams[2]]);
+        // the valid row isn't stored either
+        assert_eq!(stored.data, None);
+    }
+
+    /// Needs a database with the current schema at `DATABASE_URL`, run with `cargo test -- --ignored`.
+    #[actix_web::test]
+    #[ignore = "requires a database at DATABASE_URL"]
+    async fn closed_games_are_locked_for_referees_only() {
+        let test_year = TestYear::new().await;
+        let pool = &test_year.pool;
+
+        let game = test_year.game(test_year.create_game(1, "Kegeln")).await;
+        let team = test_year.team(1, TeamGender::Female).await;
+        test_year.close_games().await;
+
+        let row = || SetOutcome {
+            team_id: team.id,
+            data: Some("10".to_string()),
+            status: OutcomeStatus::Ok,
+            penalty_points: None,
+            penalty_millis: None,
+            version: Some(0),
+        };
+        let update = || UpdateOutcome {
+            game_id: game.id,
+            outcome: row(),
+        };
+        let referee = Outcome::set_data(update(), &UserRole::Referee, pool).await;
+        let referee_bulk =
+            Outcome::set_data_for_game(game.id, vec![row()], &UserRole::Referee, pool).await;
+        let admin = Outcome::set_data(update(), &UserRole::Admin, pool).await;
+
+        test_year.clean_up().await;
+
+        for result in [referee.map(|_| ()), referee_bulk.map(|_| ())] {
+            let err = result.err().unwrap();
+            assert_eq!(err.status_code(), StatusCode::LOCKED);
+        }
+        assert_eq!(admin.unwrap().data.as_deref(), Some("10"));
+    }
+}
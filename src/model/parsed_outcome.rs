@@ -1,87 +1,637 @@
-use super::{GameKind, Team};
+use super::{AttemptAggregation, CustomError, GameKind, Outcome, OutcomeStatus, Team};
 use humantime::parse_duration;
-use std::time::Duration;
+use std::{cmp::Ordering, fmt, time::Duration};
 use uuid::Uuid;
 
 use crate::ApiResult;
 
+/// Distances and times are stored in thousandths, so more decimals than this are rejected.
+const DISTANCE_DECIMALS: usize = 3;
+
+/// Whether a bigger or a smaller [Value] is the better outcome.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Direction {
+    HigherWins,
+    LowerWins,
+}
+
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
 pub enum Value {
     Seconds(Duration),
     Points(i32),
+    /// Millimetres.
+    Distance(i64),
+    LowestPoints(i32),
+    /// More hits are better, the time only decides between equal hits.
+    HitsWithTime {
+        hits: i32,
+        time: Duration,
+    },
+}
+
+impl Value {
+    pub fn direction(&self) -> Direction {
+        match self {
+            Value::Points(_) | Value::Distance(_) | Value::HitsWithTime { .. } => {
+                Direction::HigherWins
+            }
+            Value::Seconds(_) | Value::LowestPoints(_) => Direction::LowerWins,
+        }
+    }
+
+    /// Orders the better value first, so sorting by this puts the winner at index 0.
+    pub fn better(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (
+                Value::HitsWithTime { hits, time },
+                Value::HitsWithTime {
+                    hits: other_hits,
+                    time: other_time,
+                },
+            ) => other_hits.cmp(hits).then_with(|| time.cmp(other_time)),
+            _ => match self.direction() {
+                Direction::LowerWins => self.cmp(other),
+                Direction::HigherWins => other.cmp(self),
+            },
+        }
+    }
+
+    /// A single number for comparing the magnitude of values, i.e. seconds, points, metres or hits.
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            Value::Seconds(duration) => duration.as_secs_f64(),
+            Value::Points(points) | Value::LowestPoints(points) => *points as f64,
+            Value::Distance(millimetres) => *millimetres as f64 / 1000.0,
+            Value::HitsWithTime { hits, .. } => *hits as f64,
+        }
+    }
 }
 
 pub struct ParsedOutcome {
     pub game_id: Uuid,
     pub team: Team,
-    pub value: Value,
+    pub status: OutcomeStatus,
+    /// Only set for [OutcomeStatus::Ok], teams that didn't finish have nothing to compare.
+    pub value: Option<Value>,
     pub point_value: Option<i32>,
+    /// Set by hand, see [Outcome::override_point_value](super::Outcome::override_point_value), evaluations keep it instead of scoring the outcome.
+    pub point_override: Option<i32>,
+}
+
+/// The typed columns an [Outcome](super::Outcome) stores next to its raw data, only the ones matching the game's kind are set.
+#[derive(Default, PartialEq, Debug)]
+pub struct StoredValue {
+    /// Points, lowest points or hits.
+    pub integer: Option<i32>,
+    /// Times in milliseconds.
+    pub millis: Option<i64>,
+    /// Distances in millimetres.
+    pub millimetres: Option<i64>,
+}
+
+impl From<&Value> for StoredValue {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Points(points) | Value::LowestPoints(points) => StoredValue {
+                integer: Some(*points),
+                ..Default::default()
+            },
+            Value::Seconds(time) => StoredValue {
+                millis: Some(time.as_millis() as i64),
+                ..Default::default()
+            },
+            Value::Distance(millimetres) => StoredValue {
+                millimetres: Some(*millimetres),
+                ..Default::default()
+            },
+            Value::HitsWithTime { hits, time } => StoredValue {
+                integer: Some(*hits),
+                millis: Some(time.as_millis() as i64),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl Value {
+    /// Parse the data entered for a game of the specified kind.
+    /// Invalid data is a [CustomError::ValidationError] describing what was expected instead.
+    pub fn parse(data: &str, game_kind: &GameKind) -> ApiResult<Value> {
+        let data = data.trim();
+        let value = match game_kind {
+            GameKind::Points => data.parse::<i32>().ok().map(Value::Points),
+            GameKind::Time => parse_time(data).map(Value::Seconds),
+            GameKind::Distance => parse_millimetres(data).map(Value::Distance),
+            GameKind::LowestPoints => data.parse::<i32>().ok().map(Value::LowestPoints),
+            GameKind::HitsWithTime => data.split_once('/').and_then(|(hits, time)| {
+                Some(Value::HitsWithTime {
+                    hits: hits.trim().parse::<i32>().ok()?,
+                    time: parse_time(time)?,
+                })
+            }),
+        };
+
+        value.ok_or_else(|| CustomError::ValidationError {
+            message: format!(
+                "\"{}\" is not valid for a {}-game, expected {}.",
+                data,
+                game_kind,
+                expected(game_kind)
+            ),
+        })
+    }
+}
+
+impl Value {
+    /// Make sure the penalties can be applied to values of the specified kind and aren't negative.
+    pub fn validate_penalty(
+        game_kind: &GameKind,
+        penalty_points: Option<i32>,
+        penalty_millis: Option<i64>,
+    ) -> ApiResult<()> {
+        if penalty_points.is_some_and(|points| points < 0)
+            || penalty_millis.is_some_and(|millis| millis < 0)
+        {
+            return Err(CustomError::ValidationError {
+                message: "Penalties must not be negative.".to_string(),
+            });
+        }
+
+        let (points_allowed, millis_allowed) = match game_kind {
+            GameKind::Points | GameKind::LowestPoints => (true, false),
+            GameKind::Time => (false, true),
+            GameKind::Distance => (false, false),
+            GameKind::HitsWithTime => (true, true),
+        };
+        if (penalty_points.is_some() && !points_allowed)
+            || (penalty_millis.is_some() && !millis_allowed)
+        {
+            return Err(CustomError::ValidationError {
+                message: format!(
+                    "A {}-game only allows penalties in {}.",
+                    game_kind,
+                    match (points_allowed, millis_allowed) {
+                        (true, true) => "points and milliseconds",
+                        (true, false) => "points",
+                        (false, true) => "milliseconds",
+                        (false, false) => "nothing",
+                    }
+                ),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Combine the values of several attempts into one, `None` if there are none.
+    pub fn aggregate(values: Vec<Value>, aggregation: AttemptAggregation) -> Option<Value> {
+        let amount = values.len();
+        match aggregation {
+            AttemptAggregation::Best => values.into_iter().min_by(|a, b| a.better(b)),
+            AttemptAggregation::Sum => values.into_iter().reduce(Value::add),
+            AttemptAggregation::Average => values
+                .into_iter()
+                .reduce(Value::add)
+                .map(|sum| sum.divide(amount)),
+        }
+    }
+
+    /// Values of different kinds are never added, the first one is kept in that case.
+    fn add(self, other: Value) -> Value {
+        match (self, other) {
+            (Value::Points(a), Value::Points(b)) => Value::Points(a.saturating_add(b)),
+            (Value::LowestPoints(a), Value::LowestPoints(b)) => {
+                Value::LowestPoints(a.saturating_add(b))
+            }
+            (Value::Seconds(a), Value::Seconds(b)) => Value::Seconds(a + b),
+            (Value::Distance(a), Value::Distance(b)) => Value::Distance(a.saturating_add(b)),
+            (
+                Value::HitsWithTime { hits, time },
+                Value::HitsWithTime {
+                    hits: other_hits,
+                    time: other_time,
+                },
+            ) => Value::HitsWithTime {
+                hits: hits.saturating_add(other_hits),
+                time: time + other_time,
+            },
+            (value, _) => value,
+        }
+    }
+
+    fn divide(self, amount: usize) -> Value {
+        let divide = |value: f64| (value / amount as f64).round();
+        let divide_time =
+            |time: Duration| Duration::from_millis(divide(time.as_millis() as f64) as u64);
+        match self {
+            Value::Points(points) => Value::Points(divide(points as f64) as i32),
+            Value::LowestPoints(points) => Value::LowestPoints(divide(points as f64) as i32),
+            Value::Seconds(time) => Value::Seconds(divide_time(time)),
+            Value::Distance(millimetres) => Value::Distance(divide(millimetres as f64) as i64),
+            Value::HitsWithTime { hits, time } => Value::HitsWithTime {
+                hits: divide(hits as f64) as i32,
+                time: divide_time(time),
+            },
+        }
+    }
+
+    /// Worsen the value by the penalties, which have been validated by [Value::validate_penalty] before.
+    pub fn with_penalty(self, penalty_points: Option<i32>, penalty_millis: Option<i64>) -> Value {
+        let points = penalty_points.unwrap_or(0);
+        let time = Duration::from_millis(penalty_millis.unwrap_or(0).max(0) as u64);
+        match self {
+            Value::Points(value) => Value::Points(value.saturating_sub(points)),
+            Value::LowestPoints(value) => Value::LowestPoints(value.saturating_add(points)),
+            Value::Seconds(value) => Value::Seconds(value + time),
+            Value::Distance(value) => Value::Distance(value),
+            Value::HitsWithTime { hits, time: value } => Value::HitsWithTime {
+                hits: hits.saturating_sub(points),
+                time: value + time,
+            },
+        }
+    }
 }
 
 impl ParsedOutcome {
-    pub fn from(data: String, game_kind: &GameKind, game_id: Uuid, team: Team) -> ApiResult<Self> {
-        let value: Value = match game_kind {
-            super::GameKind::Points => Value::Points(data.parse::<i32>()?),
-            super::GameKind::Time => Value::Seconds(parse_duration(format!("{}s", data).as_str())?),
+    /// Parse an [Outcome] with its status and penalties, only [OutcomeStatus::Ok] requires data.
+    pub fn from_outcome(outcome: &Outcome, game_kind: &GameKind, team: Team) -> ApiResult<Self> {
+        let value = match (outcome.status, &outcome.data) {
+            (OutcomeStatus::Ok, Some(data)) => Some(
+                Value::parse(data, game_kind)?
+                    .with_penalty(outcome.penalty_points, outcome.penalty_millis),
+            ),
+            (OutcomeStatus::Ok, None) => {
+                return Err(CustomError::ValidationError {
+                    message: format!("The outcome {} has no data.", outcome),
+                });
+            }
+            _ => None,
         };
 
         Ok(ParsedOutcome {
-            game_id,
+            game_id: outcome.game_id,
             team,
+            status: outcome.status,
             value,
             point_value: None,
+            point_override: outcome.override_reason.as_ref().and(outcome.point_value),
         })
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::ParsedOutcome;
-    use crate::model::{Team, TeamGender};
-    use chrono::{Datelike, Local};
-    use std::time::Duration;
-    use uuid::Uuid;
+fn expected(game_kind: &GameKind) -> &'static str {
+    match game_kind {
+        GameKind::Points | GameKind::LowestPoints => "whole points like \"42\"",
+        GameKind::Time => "a time like \"93.5\", \"1:33.5\", \"1:01:33\" or \"1m 33s\"",
+        GameKind::Distance => "metres with up to three decimals like \"12.5\"",
+        GameKind::HitsWithTime => "hits and a time like \"7/93\" or \"7/1:33\"",
+    }
+}
+
+/// Parse a time given as seconds (`93.5`), minutes and seconds (`1:33.5`), hours, minutes and seconds (`1:01:33`) or with units (`1m 33s`).
+/// Times are stored in milliseconds, so anything more precise is cut off.
+fn parse_time(data: &str) -> Option<Duration> {
+    let data = data.trim();
+    if data.chars().any(|c| c.is_ascii_alphabetic()) {
+        let duration = parse_duration(data).ok()?;
+        return Some(Duration::from_millis(duration.as_millis() as u64));
+    }
 
-    fn get_team() -> Team {
-        Team {
-            id: Uuid::now_v7(),
-            trophy_id: 1,
-            name: "Team".to_string(),
-            gender: TeamGender::Female,
-            points: 0,
-            year: Local::now().year(),
+    // the last part holds the seconds, the ones before minutes and hours
+    let parts: Vec<&str> = data.split(':').collect();
+    let (seconds, larger) = parts.split_last()?;
+    if larger.len() > 2 {
+        return None;
+    }
+
+    let mut millis = parse_thousandths(seconds)?;
+    if !larger.is_empty() && millis >= 60_000 {
+        return None;
+    }
+    for (i, part) in larger.iter().rev().enumerate() {
+        if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+            return None;
         }
+        let part = part.parse::<i64>().ok()?;
+        // only hours may exceed 59, minutes wrap over into them
+        if i == 0 && larger.len() == 2 && part >= 60 {
+            return None;
+        }
+        millis = millis.checked_add(part.checked_mul(60_000 * 60_i64.pow(i as u32))?)?;
     }
 
+    Some(Duration::from_millis(millis as u64))
+}
+
+/// Parse metres with up to [DISTANCE_DECIMALS] decimals - a trailing "m" is ignored.
+fn parse_millimetres(data: &str) -> Option<i64> {
+    parse_thousandths(data.trim().trim_end_matches('m'))
+}
+
+/// Parse a positive number with up to [DISTANCE_DECIMALS] decimals, separated by either a point or a comma, as thousandths.
+fn parse_thousandths(data: &str) -> Option<i64> {
+    let data = data.trim();
+    let (whole, fraction) = data.split_once(['.', ',']).unwrap_or((data, ""));
+    if whole.is_empty()
+        || fraction.len() > DISTANCE_DECIMALS
+        || !whole.chars().all(|c| c.is_ascii_digit())
+        || !fraction.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let whole = whole.parse::<i64>().ok()?;
+    let fraction = format!("{:0<width$}", fraction, width = DISTANCE_DECIMALS)
+        .parse::<i64>()
+        .ok()?;
+    whole.checked_mul(1000)?.checked_add(fraction)
+}
+
+/// Render a time as `m:ss` or `h:mm:ss`, with milliseconds only if there are any.
+fn format_time(time: &Duration) -> String {
+    let millis = time.as_millis();
+    let (hours, minutes, seconds, millis) = (
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000,
+    );
+
+    let mut formatted = match hours {
+        0 => format!("{}:{:02}", minutes, seconds),
+        _ => format!("{}:{:02}:{:02}", hours, minutes, seconds),
+    };
+    if millis > 0 {
+        formatted.push_str(&format!(".{:03}", millis));
+    }
+    formatted
+}
+
+impl fmt::Display for Value {
+    /// The normalised form of the value, which is parsed to the same value again.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Seconds(time) => write!(f, "{}", format_time(time)),
+            Value::Points(points) | Value::LowestPoints(points) => write!(f, "{}", points),
+            Value::Distance(millimetres) => {
+                let (metres, fraction) = (millimetres / 1000, millimetres % 1000);
+                match fraction {
+                    0 => write!(f, "{}", metres),
+                    _ => write!(
+                        f,
+                        "{}.{}",
+                        metres,
+                        format!("{:03}", fraction).trim_end_matches('0')
+                    ),
+                }
+            }
+            Value::HitsWithTime { hits, time } => write!(f, "{}/{}", hits, format_time(time)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
     #[test]
     fn parse_points_positive() {
         let data = "10".to_string();
         let expected = super::Value::Points(10);
-        let actual =
-            ParsedOutcome::from(data, &super::GameKind::Points, Uuid::now_v7(), get_team())
-                .unwrap();
+        let actual = super::Value::parse(&data, &super::GameKind::Points).unwrap();
 
-        assert_eq!(expected, actual.value);
+        assert_eq!(expected, actual);
     }
 
     #[test]
     fn parse_points_negative() {
         let data = "-10".to_string();
         let expected = super::Value::Points(-10);
-        let actual =
-            ParsedOutcome::from(data, &super::GameKind::Points, Uuid::now_v7(), get_team())
-                .unwrap();
+        let actual = super::Value::parse(&data, &super::GameKind::Points).unwrap();
 
-        assert_eq!(expected, actual.value);
+        assert_eq!(expected, actual);
     }
 
     #[test]
     fn parse_seconds() {
         let data = "90".to_string();
         let expected = super::Value::Seconds(Duration::from_secs(90));
-        let actual =
-            ParsedOutcome::from(data, &super::GameKind::Time, Uuid::now_v7(), get_team()).unwrap();
+        let actual = super::Value::parse(&data, &super::GameKind::Time).unwrap();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn parse_time_formats() {
+        for (data, expected) in [
+            ("93", 93_000),
+            ("93.5", 93_500),
+            ("93,25", 93_250),
+            ("1:33", 93_000),
+            ("01:33.005", 93_005),
+            ("90:00", 5_400_000),
+            ("1:01:33", 3_693_000),
+            ("1m 30s", 90_000),
+            ("1m 30s 250ms", 90_250),
+        ] {
+            let actual = super::Value::parse(data, &super::GameKind::Time).unwrap();
+            assert_eq!(
+                super::Value::Seconds(Duration::from_millis(expected)),
+                actual,
+                "{}",
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn parse_time_invalid() {
+        for data in [
+            "",
+            "-5",
+            "1:60",
+            "1:60:00",
+            "1:2:3:4",
+            ":30",
+            "1:30.1234",
+            "abc",
+        ] {
+            assert!(
+                super::Value::parse(data, &super::GameKind::Time).is_err(),
+                "{}",
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn time_keeps_milliseconds_in_order() {
+        let faster = super::Value::parse("1:33.499", &super::GameKind::Time).unwrap();
+        let slower = super::Value::parse("93.5", &super::GameKind::Time).unwrap();
+
+        assert_eq!(faster.better(&slower), std::cmp::Ordering::Less);
+    }
+
+    #[test]
+    fn display_is_normalised() {
+        for (data, kind, expected) in [
+            ("93", super::GameKind::Time, "1:33"),
+            ("1m 30s 5ms", super::GameKind::Time, "1:30.005"),
+            ("3693.25", super::GameKind::Time, "1:01:33.250"),
+            ("12,50m", super::GameKind::Distance, "12.5"),
+            ("0.007", super::GameKind::Distance, "0.007"),
+            ("7/93", super::GameKind::HitsWithTime, "7/1:33"),
+            ("007", super::GameKind::Points, "7"),
+        ] {
+            let value = super::Value::parse(data, &kind).unwrap();
+            assert_eq!(value.to_string(), expected);
+            // the normalised form is parsed to the same value
+            assert_eq!(super::Value::parse(expected, &kind).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn parse_distance() {
+        for (data, expected) in [
+            ("12", 12_000),
+            ("12.5", 12_500),
+            ("12,05", 12_050),
+            ("0.125m", 125),
+            (" 3.1 m", 3_100),
+        ] {
+            let actual = super::Value::parse(data, &super::GameKind::Distance).unwrap();
+            assert_eq!(super::Value::Distance(expected), actual, "{}", data);
+        }
+    }
+
+    #[test]
+    fn parse_distance_invalid() {
+        for data in ["", "m", "-1", "1.2345", "1.2.3", "12cm", ".5"] {
+            assert!(
+                super::Value::parse(data, &super::GameKind::Distance).is_err(),
+                "{}",
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn parse_lowest_points() {
+        let data = "3".to_string();
+        let expected = super::Value::LowestPoints(3);
+        let actual = super::Value::parse(&data, &super::GameKind::LowestPoints).unwrap();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn parse_hits_with_time() {
+        let data = "7 / 93".to_string();
+        let expected = super::Value::HitsWithTime {
+            hits: 7,
+            time: Duration::from_secs(93),
+        };
+        let actual = super::Value::parse(&data, &super::GameKind::HitsWithTime).unwrap();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn parse_hits_without_time() {
+        let data = "7".to_string();
+        let actual = super::Value::parse(&data, &super::GameKind::HitsWithTime);
+
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn parse_invalid_is_validation_error() {
+        let actual = super::Value::parse("abc", &super::GameKind::Time);
+
+        assert!(matches!(
+            actual,
+            Err(super::CustomError::ValidationError { .. })
+        ));
+    }
+
+    #[test]
+    fn stored_value_hits_with_time() {
+        let value = super::Value::HitsWithTime {
+            hits: 7,
+            time: Duration::from_millis(93_500),
+        };
+        let expected = super::StoredValue {
+            integer: Some(7),
+            millis: Some(93_500),
+            millimetres: None,
+        };
+
+        assert_eq!(expected, super::StoredValue::from(&value));
+    }
+
+    #[test]
+    fn penalty_worsens_value() {
+        let time = super::Value::parse("1:30", &super::GameKind::Time).unwrap();
+        let hits = super::Value::parse("7/1:30", &super::GameKind::HitsWithTime).unwrap();
+
+        assert_eq!(
+            time.with_penalty(None, Some(10_000)),
+            super::Value::Seconds(Duration::from_secs(100))
+        );
+        assert_eq!(
+            hits.with_penalty(Some(2), Some(500)),
+            super::Value::HitsWithTime {
+                hits: 5,
+                time: Duration::from_millis(90_500)
+            }
+        );
+        assert_eq!(
+            super::Value::LowestPoints(3).with_penalty(Some(2), None),
+            super::Value::LowestPoints(5)
+        );
+    }
+
+    #[test]
+    fn penalty_must_fit_kind() {
+        use super::{GameKind, Value};
+
+        assert!(Value::validate_penalty(&GameKind::Time, None, Some(1000)).is_ok());
+        assert!(Value::validate_penalty(&GameKind::Time, Some(1), None).is_err());
+        assert!(Value::validate_penalty(&GameKind::Distance, None, Some(1000)).is_err());
+        assert!(Value::validate_penalty(&GameKind::Points, Some(-1), None).is_err());
+    }
+
+    #[test]
+    fn aggregate_attempts() {
+        use super::{AttemptAggregation, GameKind, Value};
+
+        let times = || {
+            ["1:30", "1:20.5", "1:40"]
+                .into_iter()
+                .map(|data| Value::parse(data, &GameKind::Time).unwrap())
+                .collect::<Vec<Value>>()
+        };
+        let seconds = |millis| Some(Value::Seconds(Duration::from_millis(millis)));
 
-        assert_eq!(expected, actual.value);
+        assert_eq!(
+            Value::aggregate(times(), AttemptAggregation::Best),
+            seconds(80_500)
+        );
+        assert_eq!(
+            Value::aggregate(times(), AttemptAggregation::Sum),
+            seconds(270_500)
+        );
+        assert_eq!(
+            Value::aggregate(times(), AttemptAggregation::Average),
+            seconds(90_167)
+        );
+        assert_eq!(
+            Value::aggregate(
+                vec![Value::Points(3), Value::Points(4)],
+                AttemptAggregation::Average
+            ),
+            Some(Value::Points(4))
+        );
+        assert_eq!(Value::aggregate(Vec::new(), AttemptAggregation::Sum), None);
     }
 }
@@ -0,0 +1,267 @@
+use super::{CustomError, Game, Team, TypeInfo};
+use crate::ApiResult;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgConnection, PgPool};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+use uuid::Uuid;
+
+/// Points the jury awards (or deducts, if negative) outside of the games, e.g. after protests or as fair-play bonus.
+/// Adjustments are part of a [Team]'s total, see [Team::recompute_points].
+#[derive(Serialize, FromRow)]
+pub struct PointAdjustment {
+    pub id: Uuid,
+    pub team_id: Uuid,
+    /// The game the adjustment concerns, if any - it's kept when the game is deleted.
+    pub game_id: Option<Uuid>,
+    pub amount: i32,
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+pub struct PointAdjustmentVec(pub Vec<PointAdjustment>);
+
+#[derive(Deserialize)]
+pub struct CreatePointAdjustment {
+    pub team_id: Uuid,
+    #[serde(default)]
+    pub game_id: Option<Uuid>,
+    pub amount: i32,
+    pub reason: String,
+}
+
+impl CreatePointAdjustment {
+    /// Validate the adjustment and return the [Team] it belongs to.
+    async fn validate(&self, pool: &PgPool) -> ApiResult<Team> {
+        if self.reason.trim().is_empty() {
+            return Err(CustomError::ValidationError {
+                message: "Every point adjustment needs a reason.".to_string(),
+            });
+        }
+        if self.amount == 0 {
+            return Err(CustomError::ValidationError {
+                message: "A point adjustment of 0 points has no effect.".to_string(),
+            });
+        }
+
+        let team = Team::find(self.team_id, &mut *pool.acquire().await?).await?;
+        if let Some(game_id) = self.game_id {
+            let game = Game::find(game_id, pool).await?;
+            if game.year != team.year {
+                return Err(CustomError::ValidationError {
+                    message: format!(
+                        "Game {} belongs to {}, but team {} to {}.",
+                        game.name, game.year, team.name, team.year
+                    ),
+                });
+            }
+        }
+
+        Ok(team)
+    }
+}
+
+impl PointAdjustment {
+    /// Find all [PointAdjustment]s of the specified year.
+    pub async fn find_all(year: i32, pool: &PgPool) -> ApiResult<PointAdjustmentVec> {
+        let adjustments = sqlx::query_as!(
+            PointAdjustment,
+            r#"SELECT point_adjustments.id, team_id, game_id, amount, reason FROM point_adjustments
+                INNER JOIN teams ON point_adjustments.team_id=teams.id
+            WHERE teams.year = $1 ORDER BY point_adjustments.id"#,
+            year
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(PointAdjustmentVec(adjustments))
+    }
+
+    /// Try to get the [PointAdjustment] of the specified ID.
+    pub async fn find(id: Uuid, pool: &PgPool) -> ApiResult<PointAdjustment> {
+        let adjustment = sqlx::query_as!(
+            PointAdjustment,
+            r#"SELECT id, team_id, game_id, amount, reason FROM point_adjustments WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        adjustment.ok_or(CustomError::NotFoundError {
+            message: format!("Point adjustment {} could not be found.", id),
+        })
+    }
+
+    /// Sum up the [PointAdjustment]s of every team of the specified year, teams without any are left out.
+    pub async fn totals(year: i32, connection: &mut PgConnection) -> ApiResult<HashMap<Uuid, i32>> {
+        let totals = sqlx::query!(
+            r#"SELECT team_id, SUM(amount)::integer as "amount!" FROM point_adjustments
+                INNER JOIN teams ON point_adjustments.team_id=teams.id
+            WHERE teams.year = $1 GROUP BY team_id"#,
+            year
+        )
+        .fetch_all(connection)
+        .await?
+        .into_iter()
+        .map(|total| (total.team_id, total.amount))
+        .collect();
+
+        Ok(totals)
+    }
+
+    /// Create a new [PointAdjustment] and update the total of its team.
+    pub async fn create(
+        create_adjustment: CreatePointAdjustment,
+        pool: &PgPool,
+    ) -> ApiResult<PointAdjustment> {
+        let team = create_adjustment.validate(pool).await?;
+
+        let mut tx = pool.begin().await?;
+        let adjustment = sqlx::query_as!(
+            PointAdjustment,
+            r#"INSERT INTO point_adjustments (id, team_id, game_id, amount, reason)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING id, team_id, game_id, amount, reason"#,
+            Uuid::now_v7(),
+            create_adjustment.team_id,
+            create_adjustment.game_id,
+            create_adjustment.amount,
+            create_adjustment.reason.trim()
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        Team::recompute_points(team.year, &mut tx).await?;
+
+        tx.commit().await?;
+        Ok(adjustment)
+    }
+
+    /// Update the specified [PointAdjustment] and the totals of the teams concerned.
+    /// Returns the previous adjustment next to the updated one.
+    pub async fn update(
+        id: Uuid,
+        altered_adjustment: CreatePointAdjustment,
+        pool: &PgPool,
+    ) -> ApiResult<(PointAdjustment, PointAdjustment)> {
+        let previous = PointAdjustment::find(id, pool).await?;
+        let team = altered_adjustment.validate(pool).await?;
+
+        let mut tx = pool.begin().await?;
+        let adjustment = sqlx::query_as!(
+            PointAdjustment,
+            r#"UPDATE point_adjustments SET team_id = $1, game_id = $2, amount = $3, reason = $4 WHERE id = $5
+                RETURNING id, team_id, game_id, amount, reason"#,
+            altered_adjustment.team_id,
+            altered_adjustment.game_id,
+            altered_adjustment.amount,
+            altered_adjustment.reason.trim(),
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        // the adjustment may have been moved to a team of another year
+        let previous_team = Team::find(previous.team_id, &mut tx).await?;
+        if previous_team.year != team.year {
+            Team::recompute_points(previous_team.year, &mut tx).await?;
+        }
+        Team::recompute_points(team.year, &mut tx).await?;
+
+        tx.commit().await?;
+        Ok((previous, adjustment))
+    }
+
+    /// Delete the specified [PointAdjustment] and update the total of its team.
+    pub async fn delete(id: Uuid, pool: &PgPool) -> ApiResult<PointAdjustment> {
+        let mut tx = pool.begin().await?;
+        let adjustment = sqlx::query_as!(
+            PointAdjustment,
+            r#"DELETE FROM point_adjustments WHERE id = $1 RETURNING id, team_id, game_id, amount, reason"#,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(CustomError::NotFoundError {
+            message: format!("Point adjustment {} could not be found.", id),
+        })?;
+        let team = Team::find(adjustment.team_id, &mut tx).await?;
+        Team::recompute_points(team.year, &mut tx).await?;
+
+        tx.commit().await?;
+        Ok(adjustment)
+    }
+}
+
+impl Display for PointAdjustment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PointAdjustment(id: {}, team_id: {}, game_id: {:?}, amount: {}, reason: {})",
+            self.id, self.team_id, self.game_id, self.amount, self.reason
+        )
+    }
+}
+
+impl TypeInfo for PointAdjustment {
+    fn type_name(&self) -> String {
+        "PointAdjustment".to_string()
+    }
+}
+
+impl Display for PointAdjustmentVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PointAdjustmentVec[{}]",
+            self.0.iter().map(|a| a.to_string()).collect::<String>()
+        )
+    }
+}
+
+impl TypeInfo for PointAdjustmentVec {
+    fn type_name(&self) -> String {
+        "PointAdjustmentVec".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{model::TeamGender, test_db::TestYear};
+
+    /// Needs a database with the current schema at `DATABASE_URL`, run with `cargo test -- --ignored`.
+    #[actix_web::test]
+    #[ignore = "requires a database at DATABASE_URL"]
+    async fn adjustments_count_in_the_totals() {
+        let test_year = TestYear::new().await;
+        let pool = &test_year.pool;
+
+        let first = test_year.team(1, TeamGender::Female).await;
+        let second = test_year.team(2, TeamGender::Male).await;
+        let adjustment = |team: &Team, amount| CreatePointAdjustment {
+            team_id: team.id,
+            game_id: None,
+            amount,
+            reason: "Protest".to_string(),
+        };
+
+        let created = PointAdjustment::create(adjustment(&first, 5), pool)
+            .await
+            .unwrap();
+        let after_create = test_year.points().await;
+        PointAdjustment::update(created.id, adjustment(&second, -3), pool)
+            .await
+            .unwrap();
+        let after_update = test_year.points().await;
+        PointAdjustment::delete(created.id, pool).await.unwrap();
+        let after_delete = test_year.points().await;
+
+        test_year.clean_up().await;
+
+        assert_eq!(after_create, vec![5, 0]);
+        // moving the adjustment to another team updates both totals
+        assert_eq!(after_update, vec![0, -3]);
+        assert_eq!(after_delete, vec![0, 0]);
+    }
+}
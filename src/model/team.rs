@@ -1,9 +1,9 @@
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool};
+use sqlx::{FromRow, PgConnection, PgPool};
 use std::fmt::{self, Display};
 use uuid::Uuid;
 
-use super::{CustomError, Game, Outcome, TypeInfo};
+use super::{Category, CustomError, Game, Outcome, TypeInfo};
 use crate::{ApiResult, model::Amount};
 
 #[derive(Serialize, Deserialize, sqlx::Type, Clone)]
@@ -18,6 +18,11 @@ pub enum TeamGender {
     #[serde(alias = "m")]
     #[serde(alias = "g")]
     Male,
+
+    #[serde(alias = "x")]
+    #[serde(alias = "mix")]
+    #[serde(alias = "gemischt")]
+    Mixed,
 }
 
 impl fmt::Display for TeamGender {
@@ -25,6 +30,7 @@ impl fmt::Display for TeamGender {
         match self {
             TeamGender::Female => write!(f, "Female"),
             TeamGender::Male => write!(f, "Male"),
+            TeamGender::Mixed => write!(f, "Mixed"),
         }
     }
 }
@@ -35,6 +41,8 @@ pub struct Team {
     pub trophy_id: i32,
     pub name: String,
     pub gender: TeamGender,
+    /// The [Category] this team is ranked in.
+    pub category_id: Uuid,
     pub points: i32,
     pub year: i32,
 }
@@ -42,11 +50,33 @@ pub struct Team {
 #[derive(Serialize)]
 pub struct TeamVec(pub Vec<Team>);
 
+/// All [Team]s of a single [Category].
+pub struct CategoryTeams {
+    pub category: Category,
+    pub teams: TeamVec,
+}
+
+/// The total of a [Team] before and after re-evaluating.
+#[derive(Serialize)]
+pub struct PointChange {
+    pub team_id: Uuid,
+    pub trophy_id: i32,
+    pub name: String,
+    pub previous_points: i32,
+    pub points: i32,
+}
+
+#[derive(Serialize)]
+pub struct PointChangeVec(pub Vec<PointChange>);
+
 #[derive(Deserialize)]
 pub struct CreateTeam {
     pub trophy_id: i32,
     pub name: String,
     pub gender: TeamGender,
+    /// See [Category::resolve] for what happens if this is omitted.
+    #[serde(default)]
+    pub category_id: Option<Uuid>,
     pub year: i32,
 }
 
@@ -71,14 +101,21 @@ pub struct ImportTeam {
     #[serde(alias = "Geschlecht")]
     #[serde(alias = "Gender")]
     pub gender: TeamGender,
+
+    #[serde(alias = "Kategorie")]
+    #[serde(alias = "Category")]
+    #[serde(alias = "Klasse")]
+    #[serde(default)]
+    pub category: Option<String>,
 }
 
 impl ImportTeam {
-    pub fn with_year(self, year: i32) -> CreateTeam {
+    pub fn with_year(self, year: i32, category_id: Option<Uuid>) -> CreateTeam {
         CreateTeam {
             trophy_id: self.id,
             name: self.name,
             gender: self.gender,
+            category_id,
             year,
         }
     }
@@ -89,7 +126,7 @@ impl Team {
     pub async fn find_all(pool: &PgPool, year: i32) -> ApiResult<TeamVec> {
         let teams = sqlx::query_as!(
             Team,
-            r#"SELECT id, trophy_id, name, gender as "gender: TeamGender", points, year FROM teams WHERE year = $1 ORDER BY id"#, year
+            r#"SELECT id, trophy_id, name, gender as "gender: TeamGender", category_id, points, year FROM teams WHERE year = $1 ORDER BY id"#, year
         )
         .fetch_all(pool)
         .await?;
@@ -104,7 +141,7 @@ impl Team {
                 SELECT DISTINCT team_id FROM game_team
                     INNER JOIN games ON game_team.game_id=games.id
                     INNER JOIN teams ON game_team.team_id=teams.id
-                WHERE data IS NULL AND teams.year = $1) AS temp"#,
+                WHERE data IS NULL AND status = 'ok' AND teams.year = $1) AS temp"#,
             year
         )
         .fetch_one(pool)
@@ -121,7 +158,7 @@ impl Team {
                 SELECT DISTINCT team_id FROM game_team
                     INNER JOIN games ON game_team.game_id=games.id
                     INNER JOIN teams ON game_team.team_id=teams.id
-                WHERE data IS NULL AND game_id = $1) AS temp"#,
+                WHERE data IS NULL AND status = 'ok' AND game_id = $1) AS temp"#,
             game_id
         )
         .fetch_one(pool)
@@ -131,30 +168,56 @@ impl Team {
         Ok(Amount(amount))
     }
 
-    /// Find all [Team]s split into a tuple by their gender.
-    pub async fn find_all_by_gender(pool: &PgPool, year: i32) -> ApiResult<(TeamVec, TeamVec)> {
-        let teams = Team::find_all(pool, year).await?.0;
-        let mut female = Vec::<Team>::new();
-        let mut male = Vec::<Team>::new();
-
-        for team in teams {
-            match team.gender {
-                TeamGender::Female => female.push(team),
-                TeamGender::Male => male.push(team),
+    /// Find all [Team]s grouped by their [Category], in the order of the categories.
+    /// Categories without teams are omitted.
+    pub async fn find_all_by_category(pool: &PgPool, year: i32) -> ApiResult<Vec<CategoryTeams>> {
+        let mut teams = Team::find_all(pool, year).await?.0;
+        let mut categories = Vec::<CategoryTeams>::new();
+
+        for category in Category::find_all(pool, year).await?.0 {
+            let (members, rest) = teams
+                .into_iter()
+                .partition(|team| team.category_id == category.id);
+            teams = rest;
+
+            if !members.is_empty() {
+                categories.push(CategoryTeams {
+                    category,
+                    teams: TeamVec(members),
+                });
             }
         }
 
-        Ok((TeamVec(female), TeamVec(male)))
+        Ok(categories)
+    }
+
+    /// Find all [Team]s that have an outcome in the specified [Game].
+    /// Like [Outcome::create], this runs as part of the caller's transaction.
+    pub async fn find_all_for_game(
+        game_id: Uuid,
+        connection: &mut PgConnection,
+    ) -> ApiResult<TeamVec> {
+        let teams = sqlx::query_as!(
+            Team,
+            r#"SELECT id, trophy_id, name, gender as "gender: TeamGender", category_id, points, year FROM teams
+                INNER JOIN game_team ON game_team.team_id=teams.id
+            WHERE game_team.game_id = $1 ORDER BY id"#,
+            game_id
+        )
+        .fetch_all(connection)
+        .await?;
+
+        Ok(TeamVec(teams))
     }
 
     /// Try to get the [Team] of the specified ID.
-    pub async fn find(id: Uuid, pool: &PgPool) -> ApiResult<Team> {
+    pub async fn find(id: Uuid, connection: &mut PgConnection) -> ApiResult<Team> {
         let team = sqlx::query_as!(
             Team,
-            r#"SELECT id, trophy_id, name, gender as "gender: TeamGender", points, year FROM teams WHERE id = $1"#,
+            r#"SELECT id, trophy_id, name, gender as "gender: TeamGender", category_id, points, year FROM teams WHERE id = $1"#,
             id
         )
-        .fetch_optional(pool)
+        .fetch_optional(connection)
         .await?;
 
         team.ok_or(CustomError::NotFoundError {
@@ -165,21 +228,30 @@ impl Team {
     /// Create a new [Team].
     pub async fn create(create_team: CreateTeam, pool: &PgPool) -> ApiResult<Team> {
         let mut tx = pool.begin().await?;
+        let category = Category::resolve(
+            create_team.category_id,
+            &create_team.gender,
+            create_team.year,
+            &mut tx,
+        )
+        .await?;
+
         let team: Team = sqlx::query_as!(
             Team,
-            r#"INSERT INTO teams (id, trophy_id, name, gender, year)
-                VALUES ($1, $2, $3, $4, $5)
-                RETURNING id, trophy_id, name, gender as "gender: TeamGender", points, year"#,
+            r#"INSERT INTO teams (id, trophy_id, name, gender, category_id, year)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                RETURNING id, trophy_id, name, gender as "gender: TeamGender", category_id, points, year"#,
             Uuid::now_v7(),
             create_team.trophy_id,
             create_team.name,
             create_team.gender as TeamGender,
+            category.id,
             create_team.year
         )
         .fetch_one(&mut *tx)
         .await?;
 
-        for game in Game::find_all(pool, create_team.year).await?.0 {
+        for game in Game::find_all(&mut tx, create_team.year).await?.0 {
             Outcome::create(game.id, team.id, &mut tx).await?;
         }
 
@@ -188,13 +260,20 @@ impl Team {
     }
 
     /// Update the specified [Team]. Does not set points.
+    /// Omitting the category keeps the current one.
     pub async fn update(id: Uuid, altered_team: CreateTeam, pool: &PgPool) -> ApiResult<Team> {
         // NOTE I've decided against being able to change the year of already created teams (for now)
         let mut tx = pool.begin().await?;
+        let team = Team::find(id, &mut tx).await?;
+        let category_id = match altered_team.category_id {
+            Some(id) => Some(Category::find_in_year(id, team.year, &mut tx).await?.id),
+            None => None,
+        };
+
         let team = sqlx::query_as!(
             Team,
-            r#"UPDATE teams SET trophy_id = $1, name = $2, gender = $3 WHERE id = $4 RETURNING id, trophy_id, name, gender as "gender: TeamGender", points, year"#,
-            altered_team.trophy_id, altered_team.name, altered_team.gender as TeamGender, id
+            r#"UPDATE teams SET trophy_id = $1, name = $2, gender = $3, category_id = COALESCE($4, category_id) WHERE id = $5 RETURNING id, trophy_id, name, gender as "gender: TeamGender", category_id, points, year"#,
+            altered_team.trophy_id, altered_team.name, altered_team.gender as TeamGender, category_id, id
         )
         .fetch_one(&mut *tx)
         .await?;
@@ -203,19 +282,31 @@ impl Team {
         Ok(team)
     }
 
-    /// Write the points of this [Team] to the database.
-    pub async fn update_points(&self, pool: &PgPool) -> ApiResult<Team> {
-        let mut tx = pool.begin().await?;
-        let team = sqlx::query_as!(
-            Team,
-            r#"UPDATE teams SET points = $1 WHERE id = $2 RETURNING id, trophy_id, name, gender as "gender: TeamGender", points, year"#,
-            self.points, self.id
+    /// Set the points of all [Team]s of the specified year to the sum of their point values and [PointAdjustment](super::PointAdjustment)s.
+    /// Only returns the teams whose points have changed.
+    /// Like [Outcome::create], this runs as part of the caller's transaction.
+    pub async fn recompute_points(
+        year: i32,
+        connection: &mut PgConnection,
+    ) -> ApiResult<PointChangeVec> {
+        // the CTE sees the points from before the update, which lets us return both
+        let changes = sqlx::query_as!(
+            PointChange,
+            r#"WITH totals AS (
+                SELECT teams.id, teams.points AS previous_points,
+                    (COALESCE((SELECT SUM(point_value) FROM game_team WHERE game_team.team_id = teams.id), 0)
+                    + COALESCE((SELECT SUM(amount) FROM point_adjustments WHERE point_adjustments.team_id = teams.id), 0))::integer AS points
+                FROM teams WHERE teams.year = $1
+            )
+            UPDATE teams SET points = totals.points FROM totals
+            WHERE teams.id = totals.id AND teams.points <> totals.points
+            RETURNING teams.id as team_id, teams.trophy_id, teams.name, totals.previous_points, teams.points"#,
+            year
         )
-        .fetch_one(&mut *tx)
+        .fetch_all(connection)
         .await?;
 
-        tx.commit().await?;
-        Ok(team)
+        Ok(PointChangeVec(changes))
     }
 
     /// Delete the specified [Team].
@@ -223,7 +314,7 @@ impl Team {
         let mut tx = pool.begin().await?;
         let team = sqlx::query_as!(
             Team,
-            r#"DELETE FROM teams WHERE id = $1 RETURNING id, trophy_id, name, gender as "gender: TeamGender", points, year"#,
+            r#"DELETE FROM teams WHERE id = $1 RETURNING id, trophy_id, name, gender as "gender: TeamGender", category_id, points, year"#,
             id
         )
         .fetch_one(&mut *tx)
@@ -238,8 +329,8 @@ impl fmt::Display for Team {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Team(id: {}, trophy_id: {}, name: {}, gender: {}, points: {})",
-            self.id, self.trophy_id, self.name, self.gender, self.points
+            "Team(id: {}, trophy_id: {}, name: {}, gender: {}, category_id: {}, points: {})",
+            self.id, self.trophy_id, self.name, self.gender, self.category_id, self.points
         )
     }
 }
@@ -265,3 +356,35 @@ impl TypeInfo for TeamVec {
         "TeamVec".to_string()
     }
 }
+
+impl Display for PointChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PointChange(team_id: {}, name: {}, previous_points: {}, points: {})",
+            self.team_id, self.name, self.previous_points, self.points
+        )
+    }
+}
+
+impl TypeInfo for PointChange {
+    fn type_name(&self) -> String {
+        "PointChange".to_string()
+    }
+}
+
+impl Display for PointChangeVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PointChangeVec[{}]",
+            self.0.iter().map(|c| c.to_string()).collect::<String>()
+        )
+    }
+}
+
+impl TypeInfo for PointChangeVec {
+    fn type_name(&self) -> String {
+        "PointChangeVec".to_string()
+    }
+}
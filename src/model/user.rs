@@ -116,9 +116,8 @@ impl User {
     pub async fn find_by_name(name: &String, pool: &PgPool) -> ApiResult<User> {
         let user = sqlx::query_as!(
             User,
-            r#"SELECT users.id, users.name, users.password, users.role as "role: UserRole", game_id, games.name as "game_name?", users.session FROM users
-            LEFT JOIN games ON users.game_id=games.id
-            WHERE users.name = $1"#, name
+            r#"SELECT id, name, password, role as "role: UserRole", game_id, (SELECT games.name FROM games WHERE games.id=users.game_id) as "game_name?", session FROM users
+            WHERE name = $1"#, name
         ).fetch_optional(pool)
         .await?;
 
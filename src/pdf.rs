@@ -0,0 +1,183 @@
+use pdf_writer::{Content, Name, Pdf, Rect, Ref, Str, TextStr};
+
+/// A4 in points.
+pub const PAGE_WIDTH: f32 = 595.0;
+pub const PAGE_HEIGHT: f32 = 842.0;
+pub const MARGIN: f32 = 56.0;
+
+const REGULAR: Name = Name(b"F1");
+const BOLD: Name = Name(b"F2");
+
+/// The widths of the printable ASCII characters of Helvetica in thousandths of the font size.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+#[derive(Clone, Copy)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+/// A document of A4-pages using the standard fonts every PDF reader ships, so no fonts have to be embedded.
+pub struct Document {
+    title: String,
+    pages: Vec<Page>,
+}
+
+pub struct Page(Content);
+
+impl Document {
+    pub fn new(title: &str) -> Document {
+        Document {
+            title: title.to_string(),
+            pages: Vec::new(),
+        }
+    }
+
+    pub fn add_page(&mut self) -> &mut Page {
+        self.pages.push(Page(Content::new()));
+        self.pages.last_mut().expect("a page was just added")
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        let catalog_id = Ref::new(1);
+        let page_tree_id = Ref::new(2);
+        let regular_id = Ref::new(3);
+        let bold_id = Ref::new(4);
+        let info_id = Ref::new(5);
+        // every page needs an id for itself and one for its content
+        let page_ids: Vec<(Ref, Ref)> = (0..self.pages.len() as i32)
+            .map(|i| (Ref::new(6 + 2 * i), Ref::new(7 + 2 * i)))
+            .collect();
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id)
+            .kids(page_ids.iter().map(|(page_id, _)| *page_id))
+            .count(page_ids.len() as i32);
+        for (font_id, font) in [(regular_id, "Helvetica"), (bold_id, "Helvetica-Bold")] {
+            pdf.type1_font(font_id)
+                .base_font(Name(font.as_bytes()))
+                .encoding_predefined(Name(b"WinAnsiEncoding"));
+        }
+        pdf.document_info(info_id).title(TextStr(&self.title));
+
+        for (Page(content), (page_id, content_id)) in self.pages.into_iter().zip(page_ids) {
+            let mut page = pdf.page(page_id);
+            page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT))
+                .parent(page_tree_id)
+                .contents(content_id);
+            page.resources()
+                .fonts()
+                .pair(REGULAR, regular_id)
+                .pair(BOLD, bold_id);
+            drop(page);
+            pdf.stream(content_id, &content.finish());
+        }
+
+        pdf.finish()
+    }
+}
+
+impl Page {
+    /// Write the text starting at the specified position, measured from the bottom left of the page.
+    pub fn text(&mut self, x: f32, y: f32, font: Font, size: f32, text: &str) -> &mut Self {
+        let font = match font {
+            Font::Regular => REGULAR,
+            Font::Bold => BOLD,
+        };
+        self.0
+            .begin_text()
+            .set_font(font, size)
+            .next_line(x, y)
+            .show(Str(&win_ansi(text)))
+            .end_text();
+        self
+    }
+
+    /// Write the text centered on the page, always in [Font::Regular].
+    pub fn text_centered(&mut self, y: f32, size: f32, text: &str) -> &mut Self {
+        let x = (PAGE_WIDTH - text_width(text, size)) / 2.0;
+        self.text(x, y, Font::Regular, size, text)
+    }
+
+    /// Draw a horizontal line between the margins.
+    pub fn rule(&mut self, y: f32) -> &mut Self {
+        self.0
+            .set_line_width(0.5)
+            .move_to(MARGIN, y)
+            .line_to(PAGE_WIDTH - MARGIN, y)
+            .stroke();
+        self
+    }
+}
+
+/// Estimate the width of the text in Helvetica, characters besides ASCII are as wide as most letters.
+fn text_width(text: &str, size: f32) -> f32 {
+    let width: u32 = text
+        .chars()
+        .map(|c| match c {
+            ' '..='~' => HELVETICA_WIDTHS[c as usize - ' ' as usize] as u32,
+            _ => 556,
+        })
+        .sum();
+
+    width as f32 * size / 1000.0
+}
+
+/// Encode the text for the standard fonts, characters they don't contain are replaced by `?`.
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            ' '..='~' | '\u{a0}'..='\u{ff}' => c as u8,
+            '€' => 0x80,
+            '‚' => 0x82,
+            '„' => 0x84,
+            '…' => 0x85,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '–' => 0x96,
+            '—' => 0x97,
+            _ => b'?',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_is_encoded_for_the_standard_fonts() {
+        assert_eq!(win_ansi("Team 1"), b"Team 1");
+        assert_eq!(win_ansi("Füchse – Süß"), b"F\xfcchse \x96 S\xfc\xdf");
+        assert_eq!(win_ansi("Łódź"), b"?\xf3d?");
+    }
+
+    #[test]
+    fn text_width_follows_helvetica() {
+        assert_eq!(text_width("", 12.0), 0.0);
+        assert_eq!(text_width("10", 10.0), 11.12);
+        assert_eq!(text_width("ä", 1000.0), 556.0);
+    }
+
+    #[test]
+    fn documents_contain_every_page() {
+        let mut document = Document::new("Ergebnisse");
+        document
+            .add_page()
+            .text(MARGIN, MARGIN, Font::Bold, 12.0, "Seite 1");
+        document.add_page().text_centered(MARGIN, 12.0, "Seite 2");
+
+        let pdf = document.finish();
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert!(String::from_utf8_lossy(&pdf).contains("/Count 2"));
+    }
+}
@@ -0,0 +1,324 @@
+use std::{cmp::Ordering, collections::HashMap};
+use uuid::Uuid;
+
+use crate::{
+    eval::places,
+    model::{Outcome, ParsedOutcome, PlaceGap, Team, TieBreaker},
+};
+
+/// What a team achieved in the single games, used to break ties between equal totals.
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct Record {
+    /// Games in which the team took first place within its category, shared first places count for everybody.
+    pub wins: i32,
+    /// The best place in any game, shared places count for everybody.
+    /// Places are determined from the values like in the evaluation, so they don't depend on the scoring or overrides.
+    pub best_place: Option<i32>,
+    /// The point value in the designated tiebreaker-game.
+    pub tiebreaker_points: Option<i32>,
+}
+
+pub struct Ranked {
+    pub place: i32,
+    pub team: Team,
+    pub record: Record,
+}
+
+/// Rank the teams of a single category by their points, breaking ties by applying the rules in order.
+/// Teams that are equal in every respect share a place, the gap decides whether the following places are skipped, just like in the evaluation.
+/// Only outcomes of the given teams with a point value are taken into account.
+pub fn rank(
+    teams: Vec<Team>,
+    outcomes: &[Outcome],
+    rules: &[TieBreaker],
+    tiebreaker_game_id: Option<Uuid>,
+    gap: &PlaceGap,
+) -> Vec<Ranked> {
+    let mut records = records(&teams, outcomes, tiebreaker_game_id, gap);
+    let mut ranked: Vec<Ranked> = teams
+        .into_iter()
+        .map(|team| Ranked {
+            record: records.remove(&team.id).unwrap_or_default(),
+            team,
+            place: 0,
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| compare(a, b, rules));
+
+    // always start at place 1
+    let mut place = 1;
+    for i in 0..ranked.len() {
+        if i > 0 && compare(&ranked[i - 1], &ranked[i], rules) != Ordering::Equal {
+            place = match gap {
+                PlaceGap::Skip => i as i32 + 1,
+                PlaceGap::Dense => place + 1,
+            };
+        }
+        ranked[i].place = place;
+    }
+
+    ranked
+}
+
+/// Orders the better team first.
+fn compare(a: &Ranked, b: &Ranked, rules: &[TieBreaker]) -> Ordering {
+    let mut ordering = b.team.points.cmp(&a.team.points);
+
+    for rule in rules {
+        ordering = ordering.then_with(|| match rule {
+            TieBreaker::Wins => b.record.wins.cmp(&a.record.wins),
+            // lower places are better and teams without any place come last
+            TieBreaker::BestPlacement => a
+                .record
+                .best_place
+                .unwrap_or(i32::MAX)
+                .cmp(&b.record.best_place.unwrap_or(i32::MAX)),
+            // None is less than Some, so teams without points in that game come last
+            TieBreaker::Game => b.record.tiebreaker_points.cmp(&a.record.tiebreaker_points),
+        });
+    }
+
+    ordering
+}
+
+fn records(
+    teams: &[Team],
+    outcomes: &[Outcome],
+    tiebreaker_game_id: Option<Uuid>,
+    gap: &PlaceGap,
+) -> HashMap<Uuid, Record> {
+    let mut records: HashMap<Uuid, Record> = teams
+        .iter()
+        .map(|team| (team.id, Record::default()))
+        .collect();
+
+    // places are determined per game among the teams that are ranked together
+    let mut games = HashMap::<Uuid, Vec<ParsedOutcome>>::new();
+    for outcome in outcomes {
+        let Some(team) = teams.iter().find(|team| team.id == outcome.team_id) else {
+            continue;
+        };
+        // only evaluated outcomes count, data that can't be parsed anymore has no place
+        if outcome.point_value.is_none() {
+            continue;
+        }
+        if let Ok(mut parsed) =
+            ParsedOutcome::from_outcome(outcome, &outcome.game_kind, team.clone())
+        {
+            parsed.point_value = outcome.point_value;
+            games.entry(outcome.game_id).or_default().push(parsed);
+        }
+    }
+
+    for (game_id, mut outcomes) in games {
+        let game_places = places(&mut outcomes, gap);
+        for (outcome, place) in outcomes.iter().zip(game_places) {
+            let record = records.get_mut(&outcome.team.id).unwrap();
+
+            // teams that didn't finish can't win, even if nobody did
+            if place == 1 && outcome.value.is_some() {
+                record.wins += 1;
+            }
+            record.best_place = Some(record.best_place.map_or(place, |best| best.min(place)));
+            if Some(game_id) == tiebreaker_game_id {
+                record.tiebreaker_points = outcome.point_value;
+            }
+        }
+    }
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DEFAULT_TIE_BREAKERS, GameKind, OutcomeStatus, TeamGender};
+
+    fn get_team(trophy_id: i32, points: i32) -> Team {
+        Team {
+            id: Uuid::now_v7(),
+            trophy_id,
+            name: format!("Team {}", trophy_id),
+            gender: TeamGender::Female,
+            category_id: Uuid::nil(),
+            points,
+            year: 2024,
+        }
+    }
+
+    fn get_outcome(game_id: Uuid, team: &Team, point_value: i32) -> Outcome {
+        Outcome {
+            game_id,
+            game_trophy_id: 1,
+            game_name: "Game".to_string(),
+            game_kind: GameKind::Points,
+            team_id: team.id,
+            team_trophy_id: team.trophy_id,
+            team_name: team.name.clone(),
+            team_gender: team.gender.clone(),
+            data: Some(point_value.to_string()),
+            value_integer: Some(point_value),
+            value_millis: None,
+            value_millimetres: None,
+            status: OutcomeStatus::Ok,
+            penalty_points: None,
+            penalty_millis: None,
+            point_value: Some(point_value),
+            version: 0,
+            override_reason: None,
+        }
+    }
+
+    /// Build outcomes from a table of point values, one row per game and one column per team.
+    fn get_outcomes(teams: &[Team], table: &[&[i32]]) -> (Vec<Uuid>, Vec<Outcome>) {
+        let mut game_ids = Vec::new();
+        let mut outcomes = Vec::new();
+        for row in table {
+            let game_id = Uuid::now_v7();
+            game_ids.push(game_id);
+            for (team, point_value) in teams.iter().zip(row.iter()) {
+                outcomes.push(get_outcome(game_id, team, *point_value));
+            }
+        }
+        (game_ids, outcomes)
+    }
+
+    fn places(ranked: &[Ranked]) -> Vec<(i32, i32)> {
+        ranked.iter().map(|r| (r.team.trophy_id, r.place)).collect()
+    }
+
+    #[test]
+    fn rank_without_rules_shares_places() {
+        let teams = vec![get_team(1, 90), get_team(2, 100), get_team(3, 90)];
+        let (_, outcomes) = get_outcomes(&teams, &[&[50, 50, 40], &[40, 50, 50]]);
+
+        let ranked = rank(teams, &outcomes, &[], None, &PlaceGap::Dense);
+        assert_eq!(places(&ranked), vec![(2, 1), (1, 2), (3, 2)]);
+    }
+
+    #[test]
+    fn rank_three_way_tie_by_wins() {
+        // all teams have 147 points, but team 3 won twice and team 1 once
+        let teams = vec![get_team(1, 147), get_team(2, 147), get_team(3, 147)];
+        let (_, outcomes) = get_outcomes(&teams, &[&[50, 49, 48], &[48, 49, 50], &[49, 49, 50]]);
+
+        let ranked = rank(
+            teams,
+            &outcomes,
+            &DEFAULT_TIE_BREAKERS,
+            None,
+            &PlaceGap::Dense,
+        );
+        assert_eq!(places(&ranked), vec![(3, 1), (1, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn rank_four_way_tie_by_best_placement() {
+        // only team 5 won, team 1 and 2 were second once - team 3 and 4 were third at best
+        let teams = vec![
+            get_team(1, 80),
+            get_team(2, 80),
+            get_team(3, 80),
+            get_team(4, 80),
+            get_team(5, 100),
+        ];
+        let (_, outcomes) = get_outcomes(&teams, &[&[45, 35, 40, 39, 50], &[35, 45, 40, 41, 50]]);
+
+        let ranked = rank(
+            teams,
+            &outcomes,
+            &DEFAULT_TIE_BREAKERS,
+            None,
+            &PlaceGap::Dense,
+        );
+        assert_eq!(
+            places(&ranked),
+            vec![(5, 1), (1, 2), (2, 2), (3, 3), (4, 3)]
+        );
+    }
+
+    #[test]
+    fn rank_tie_by_tiebreaker_game() {
+        let teams = vec![get_team(1, 90), get_team(2, 90), get_team(3, 90)];
+        let (game_ids, outcomes) = get_outcomes(&teams, &[&[50, 40, 45], &[40, 50, 45]]);
+
+        let ranked = rank(
+            teams,
+            &outcomes,
+            &DEFAULT_TIE_BREAKERS,
+            Some(game_ids[1]),
+            &PlaceGap::Dense,
+        );
+        assert_eq!(places(&ranked), vec![(2, 1), (1, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn rank_applies_rules_in_order() {
+        // team 1 won once, team 2 never won but has the better tiebreaker-game
+        let teams = vec![get_team(1, 90), get_team(2, 90), get_team(3, 100)];
+        let (game_ids, outcomes) = get_outcomes(&teams, &[&[50, 49, 40], &[40, 41, 60]]);
+
+        let by_game = rank(
+            teams.clone(),
+            &outcomes,
+            &[TieBreaker::Game, TieBreaker::Wins],
+            Some(game_ids[1]),
+            &PlaceGap::Dense,
+        );
+        assert_eq!(places(&by_game), vec![(3, 1), (2, 2), (1, 3)]);
+
+        let by_wins = rank(
+            teams,
+            &outcomes,
+            &[TieBreaker::Wins, TieBreaker::Game],
+            Some(game_ids[1]),
+            &PlaceGap::Dense,
+        );
+        assert_eq!(places(&by_wins), vec![(3, 1), (1, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn rank_skips_shared_places() {
+        let teams = vec![
+            get_team(1, 100),
+            get_team(2, 90),
+            get_team(3, 90),
+            get_team(4, 80),
+        ];
+        let (_, outcomes) = get_outcomes(&teams, &[&[50, 45, 45, 40]]);
+
+        let ranked = rank(teams, &outcomes, &[], None, &PlaceGap::Skip);
+        assert_eq!(places(&ranked), vec![(1, 1), (2, 2), (3, 2), (4, 4)]);
+    }
+
+    #[test]
+    fn rank_places_in_games_follow_the_values() {
+        // podium-scoring gives no points to team 4 and 5, but team 4 was better in the game
+        let teams = vec![
+            get_team(1, 3),
+            get_team(2, 2),
+            get_team(3, 1),
+            get_team(4, 0),
+            get_team(5, 0),
+        ];
+        let (_, mut outcomes) = get_outcomes(&teams, &[&[3, 2, 1, 0, 0]]);
+        for (outcome, value) in outcomes.iter_mut().zip([50, 40, 30, 20, 10]) {
+            outcome.data = Some(value.to_string());
+            outcome.value_integer = Some(value);
+        }
+
+        let ranked = rank(
+            teams,
+            &outcomes,
+            &[TieBreaker::BestPlacement],
+            None,
+            &PlaceGap::Skip,
+        );
+        assert_eq!(
+            places(&ranked),
+            vec![(1, 1), (2, 2), (3, 3), (4, 4), (5, 5)]
+        );
+        assert_eq!(ranked[4].record.best_place, Some(5));
+    }
+}
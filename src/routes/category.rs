@@ -0,0 +1,77 @@
+use actix_web::{
+    Responder, delete, get, post, put,
+    web::{self, Data, Query},
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    ApiResult, ToJson,
+    middleware::Authenticated,
+    model::{Category, CreateCategory, UserRole, Year},
+};
+
+#[get("/categories")]
+async fn find_all_categories(
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    year: Query<Year>,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin, UserRole::Visualizer])?;
+    Category::find_all(&pool, **year).await?.to_json()
+}
+
+#[post("/categories")]
+async fn create_category(
+    create_category: web::Json<CreateCategory>,
+    pool: Data<PgPool>,
+    auth: Authenticated,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    Category::create(create_category.into_inner(), &pool)
+        .await?
+        .to_json()
+}
+
+#[get("/categories/{id}")]
+async fn find_category(
+    id: web::Path<Uuid>,
+    pool: Data<PgPool>,
+    auth: Authenticated,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin, UserRole::Visualizer])?;
+    Category::find(*id, &mut *pool.acquire().await?)
+        .await?
+        .to_json()
+}
+
+#[put("/categories/{id}")]
+async fn update_category(
+    id: web::Path<Uuid>,
+    category: web::Json<CreateCategory>,
+    pool: Data<PgPool>,
+    auth: Authenticated,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    Category::update(*id, category.into_inner(), &pool)
+        .await?
+        .to_json()
+}
+
+#[delete("/categories/{id}")]
+async fn delete_category(
+    id: web::Path<Uuid>,
+    pool: Data<PgPool>,
+    auth: Authenticated,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    Category::delete(*id, &pool).await?.to_json()
+}
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    cfg.service(find_all_categories);
+    cfg.service(create_category);
+    cfg.service(find_category);
+    cfg.service(update_category);
+    cfg.service(delete_category);
+}
@@ -0,0 +1,41 @@
+use actix_web::{
+    Responder, get, put,
+    web::{self, Data, Json, Path},
+};
+use sqlx::PgPool;
+
+use crate::{
+    ApiResult, ToJson,
+    middleware::Authenticated,
+    model::{TrophyConfig, UpdateTrophyConfig, UserRole},
+};
+
+#[get("/years/{year}/config")]
+async fn find_config(
+    year: Path<i32>,
+    pool: Data<PgPool>,
+    auth: Authenticated,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    TrophyConfig::find(*year, &mut *pool.acquire().await?)
+        .await?
+        .to_json()
+}
+
+#[put("/years/{year}/config")]
+async fn update_config(
+    year: Path<i32>,
+    config: Json<UpdateTrophyConfig>,
+    pool: Data<PgPool>,
+    auth: Authenticated,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    TrophyConfig::update(*year, config.into_inner(), &pool)
+        .await?
+        .to_json()
+}
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    cfg.service(find_config);
+    cfg.service(update_config);
+}
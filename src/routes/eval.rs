@@ -1,15 +1,20 @@
-use actix_files::NamedFile;
 use actix_web::{
-    HttpResponse, Responder, get,
+    HttpResponse, Responder, get, post,
     web::{self, Data, Query},
 };
 use sqlx::PgPool;
+use uuid::Uuid;
 
 use crate::{
     ApiResult, ToJson,
-    eval::{create_xlsx_file, evaluate_trophy},
+    check::check_trophy,
+    eval::{evaluate_single_game, evaluate_trophy, preview_trophy, recompute_trophy, standings},
+    export::{
+        ResultFile, create_certificates_pdf, create_csv_file, create_results_pdf, create_xlsx_file,
+        find_results,
+    },
     middleware::Authenticated,
-    model::{StatusResponse, UserRole, Year},
+    model::{LangQuery, StatusResponse, Team, UserRole, Year},
 };
 
 #[get("/eval")]
@@ -23,14 +28,121 @@ async fn evaluate(
     Ok(HttpResponse::Ok())
 }
 
+#[post("/eval/recompute")]
+async fn recompute(
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    year: Query<Year>,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    recompute_trophy(&pool, **year).await?.to_json()
+}
+
+#[post("/games/{id}/eval")]
+async fn evaluate_game(
+    id: web::Path<Uuid>,
+    pool: Data<PgPool>,
+    auth: Authenticated,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    evaluate_single_game(*id, &pool).await?.to_json()
+}
+
+#[get("/standings")]
+async fn find_standings(
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    year: Query<Year>,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin, UserRole::Visualizer])?;
+    standings(&pool, **year).await?.to_json()
+}
+
+#[get("/eval/check")]
+async fn check(
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    year: Query<Year>,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    check_trophy(&pool, **year).await?.to_json()
+}
+
+#[get("/eval/preview")]
+async fn preview(
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    year: Query<Year>,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    preview_trophy(&pool, **year).await?.to_json()
+}
+
 #[get("/eval/sheet")]
 async fn download_sheet(
     pool: Data<PgPool>,
     auth: Authenticated,
     year: Query<Year>,
-) -> ApiResult<NamedFile> {
+    lang: Query<LangQuery>,
+) -> ApiResult<ResultFile> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    create_xlsx_file(&pool, **year, **lang).await
+}
+
+#[get("/eval/results.csv")]
+async fn download_csv(
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    year: Query<Year>,
+    lang: Query<LangQuery>,
+) -> ApiResult<ResultFile> {
+    auth.has_roles(vec![UserRole::Admin, UserRole::Visualizer])?;
+    create_csv_file(&pool, **year, **lang).await
+}
+
+#[get("/eval/results.json")]
+async fn find_results_json(
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    year: Query<Year>,
+    lang: Query<LangQuery>,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin, UserRole::Visualizer])?;
+    find_results(&pool, **year, **lang).await?.to_json()
+}
+
+#[get("/eval/pdf/results")]
+async fn download_results_pdf(
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    year: Query<Year>,
+    lang: Query<LangQuery>,
+) -> ApiResult<ResultFile> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    create_results_pdf(&pool, **year, **lang).await
+}
+
+#[get("/eval/pdf/certificates")]
+async fn download_certificates(
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    year: Query<Year>,
+    lang: Query<LangQuery>,
+) -> ApiResult<ResultFile> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    create_certificates_pdf(&pool, **year, None, **lang).await
+}
+
+#[get("/eval/pdf/certificates/{id}")]
+async fn download_certificate(
+    id: web::Path<Uuid>,
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    lang: Query<LangQuery>,
+) -> ApiResult<ResultFile> {
     auth.has_roles(vec![UserRole::Admin])?;
-    Ok(create_xlsx_file(&pool, **year).await?.0)
+    let team = Team::find(*id, &mut *pool.acquire().await?).await?;
+    create_certificates_pdf(&pool, team.year, Some(team.id), **lang).await
 }
 
 #[get("/eval/done")]
@@ -40,12 +152,22 @@ async fn is_evaluated(
     year: Query<Year>,
 ) -> ApiResult<impl Responder> {
     auth.has_roles(vec![UserRole::Admin])?;
-    let status = crate::eval::is_evaluated(&pool, **year).await?;
+    let status = crate::eval::is_evaluated(&mut *pool.acquire().await?, **year).await?;
     StatusResponse { status }.to_json()
 }
 
 pub fn init(cfg: &mut web::ServiceConfig) {
     cfg.service(evaluate);
+    cfg.service(recompute);
+    cfg.service(check);
+    cfg.service(preview);
+    cfg.service(evaluate_game);
+    cfg.service(find_standings);
     cfg.service(download_sheet);
+    cfg.service(download_csv);
+    cfg.service(find_results_json);
+    cfg.service(download_results_pdf);
+    cfg.service(download_certificates);
+    cfg.service(download_certificate);
     cfg.service(is_evaluated);
 }
@@ -1,7 +1,7 @@
 use crate::{
     ApiResult, ToJson,
     middleware::Authenticated,
-    model::{CreateGame, Game, Team, UserRole, Year},
+    model::{CreateGame, CustomError, Game, Team, User, UserRole, Year},
 };
 use actix_web::{
     Responder, delete, get, post, put,
@@ -17,7 +17,9 @@ async fn find_all_games(
     year: Query<Year>,
 ) -> ApiResult<impl Responder> {
     auth.has_roles(vec![UserRole::Admin, UserRole::Visualizer])?;
-    Game::find_all(&pool, **year).await?.to_json()
+    Game::find_all(&mut *pool.acquire().await?, **year)
+        .await?
+        .to_json()
 }
 
 #[get("/games/pending/amount")]
@@ -27,7 +29,9 @@ async fn games_pending(
     year: Query<Year>,
 ) -> ApiResult<impl Responder> {
     auth.has_roles(vec![UserRole::Admin, UserRole::Visualizer])?;
-    Game::find_all_pending(**year, &pool).await?.to_json()
+    Game::find_all_pending(**year, &mut *pool.acquire().await?)
+        .await?
+        .to_json()
 }
 
 #[post("/games")]
@@ -83,6 +87,37 @@ async fn pending_teams_amount(
     Team::find_all_pending_for_game(*id, &pool).await?.to_json()
 }
 
+/// Referees close their game once all outcomes are entered.
+#[post("/games/{id}/close")]
+async fn close_game(
+    id: Path<Uuid>,
+    pool: Data<PgPool>,
+    auth: Authenticated,
+) -> ApiResult<impl Responder> {
+    match auth.role {
+        UserRole::Admin => Game::set_closed(*id, true, &pool).await?.to_json(),
+        UserRole::Referee => {
+            if *id == User::find_game_for_ref(auth.id, &pool).await?.id {
+                Game::set_closed(*id, true, &pool).await?.to_json()
+            } else {
+                Err(CustomError::AccessDeniedError)
+            }
+        }
+        UserRole::Visualizer => Err(CustomError::AccessDeniedError),
+    }
+}
+
+/// Only admins can reopen a game, e.g. to let the referee correct an outcome.
+#[post("/games/{id}/reopen")]
+async fn reopen_game(
+    id: Path<Uuid>,
+    pool: Data<PgPool>,
+    auth: Authenticated,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    Game::set_closed(*id, false, &pool).await?.to_json()
+}
+
 // NOTE order matters!
 pub fn init(cfg: &mut web::ServiceConfig) {
     cfg.service(find_all_games);
@@ -92,4 +127,6 @@ pub fn init(cfg: &mut web::ServiceConfig) {
     cfg.service(update_game);
     cfg.service(delete_game);
     cfg.service(pending_teams_amount);
+    cfg.service(close_game);
+    cfg.service(reopen_game);
 }
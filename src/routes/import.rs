@@ -1,7 +1,7 @@
 use crate::{
     ApiResult,
     middleware::Authenticated,
-    model::{ImportTeam, ImportUpload, Team, UserRole},
+    model::{Category, ImportTeam, ImportUpload, Team, UserRole},
 };
 use actix_multipart::form::MultipartForm;
 use actix_web::{
@@ -27,16 +27,29 @@ async fn read_sheet(form: ImportUpload, pool: &PgPool) -> ApiResult<()> {
     let mut workbook: Xlsx<_> = open_workbook(form.file.file.path())?;
     let range = workbook.worksheet_range(&form.metadata.sheet_name)?;
 
-    let iter_records = RangeDeserializerBuilder::with_headers(&[
+    let mut headers = vec![
         &form.metadata.trophy_id_header,
         &form.metadata.name_header,
         &form.metadata.gender_header,
-    ])
-    .from_range(&range)?;
+    ];
+    if let Some(category_header) = &form.metadata.category_header {
+        headers.push(category_header);
+    }
+
+    let iter_records = RangeDeserializerBuilder::with_headers(&headers).from_range(&range)?;
 
     for result in iter_records {
         let team: ImportTeam = result?;
-        Team::create(team.with_year(form.metadata.year), pool).await?;
+        // categories are referenced by name in the sheet and created on demand
+        let category_id = match &team.category {
+            Some(name) => Some(
+                Category::find_or_create(name, form.metadata.year, &mut *pool.acquire().await?)
+                    .await?
+                    .id,
+            ),
+            None => None,
+        };
+        Team::create(team.with_year(form.metadata.year, category_id), pool).await?;
     }
 
     Ok(())
@@ -1,19 +1,25 @@
 use actix_web::web;
 
+mod category;
+mod config;
 mod eval;
 mod game;
 mod history;
 mod import;
 mod misc;
 mod outcome;
+mod point_adjustment;
 mod team;
 mod user;
 
 pub fn init(cfg: &mut web::ServiceConfig) {
     misc::init(cfg);
+    config::init(cfg);
+    category::init(cfg);
     game::init(cfg);
     team::init(cfg);
     outcome::init(cfg);
+    point_adjustment::init(cfg);
     eval::init(cfg);
     user::init(cfg);
     history::init(cfg);
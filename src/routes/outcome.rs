@@ -1,5 +1,7 @@
 use actix_web::{
-    Responder, get, put,
+    HttpMessage, HttpRequest, Responder, get,
+    http::header::{ETAG, IF_MATCH},
+    put,
     web::{self, Data},
 };
 use sqlx::PgPool;
@@ -7,8 +9,11 @@ use uuid::Uuid;
 
 use crate::{
     ApiResult, ToJson,
-    middleware::Authenticated,
-    model::{CustomError, Outcome, User, UserRole},
+    middleware::{Authenticated, OperationDetail},
+    model::{
+        Attempt, CustomError, Outcome, OverridePointValue, SetAttempt, SetOutcome, UpdateOutcome,
+        User, UserRole,
+    },
 };
 
 #[get("/outcomes")]
@@ -17,26 +22,109 @@ async fn find_all_outcomes(pool: Data<PgPool>, auth: Authenticated) -> ApiResult
     Outcome::find_all(&pool).await?.to_json()
 }
 
+/// Read the version of an outcome from the `If-Match`-header, e.g. `"3"` or `W/"3"`.
+fn version_from_if_match(req: &HttpRequest) -> ApiResult<Option<i32>> {
+    let Some(header) = req.headers().get(IF_MATCH) else {
+        return Ok(None);
+    };
+    let tag = header
+        .to_str()
+        .map_err(|err| CustomError::ValidationError {
+            message: format!("Invalid If-Match-header: {}", err),
+        })?;
+    let version = tag
+        .trim()
+        .trim_start_matches("W/")
+        .trim_matches('"')
+        .parse::<i32>()
+        .map_err(|_| CustomError::ValidationError {
+            message: format!(
+                "The If-Match-header '{}' is not a version of an outcome.",
+                tag
+            ),
+        })?;
+    Ok(Some(version))
+}
+
 /// Outcomes are automatically initialized , thus we only need an update-method().
+/// Referees can't change the outcomes of their game anymore once they have closed it.
+/// The version the update is based on is taken from the `If-Match`-header if present, otherwise from the body.
 #[put("/outcomes")]
 async fn update_outcome(
     pool: Data<PgPool>,
     auth: Authenticated,
-    outcome: web::Json<Outcome>,
+    req: HttpRequest,
+    update: web::Json<UpdateOutcome>,
 ) -> ApiResult<impl Responder> {
+    let mut update = update.into_inner();
+    if let Some(version) = version_from_if_match(&req)? {
+        update.outcome.version = Some(version);
+    }
+
     match auth.role {
-        UserRole::Admin => outcome.into_inner().set_data(&pool).await?.to_json(),
+        UserRole::Admin => {}
         UserRole::Referee => {
             let game = User::find_game_for_ref(auth.id, &pool).await?;
 
-            if outcome.game_id == game.id {
-                outcome.into_inner().set_data(&pool).await?.to_json()
-            } else {
-                Err(CustomError::AccessDeniedError)
+            if update.game_id != game.id {
+                return Err(CustomError::AccessDeniedError);
             }
         }
-        UserRole::Visualizer => Err(CustomError::AccessDeniedError),
+        UserRole::Visualizer => return Err(CustomError::AccessDeniedError),
+    }
+    let updated = Outcome::set_data(update, &auth.role, &pool).await?;
+
+    let etag = format!("\"{}\"", updated.version);
+    Ok(updated.to_json()?.customize().insert_header((ETAG, etag)))
+}
+
+/// Submit the outcomes of several teams for a game at once, either all of them are stored or none.
+#[put("/games/{id}/outcomes")]
+async fn update_outcomes_for_game(
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    game_id: web::Path<Uuid>,
+    outcomes: web::Json<Vec<SetOutcome>>,
+) -> ApiResult<impl Responder> {
+    match auth.role {
+        UserRole::Admin => {}
+        UserRole::Referee => {
+            let game = User::find_game_for_ref(auth.id, &pool).await?;
+
+            if *game_id != game.id {
+                return Err(CustomError::AccessDeniedError);
+            }
+        }
+        UserRole::Visualizer => return Err(CustomError::AccessDeniedError),
     }
+
+    Outcome::set_data_for_game(*game_id, outcomes.into_inner(), &auth.role, &pool)
+        .await?
+        .to_json()
+}
+
+/// Manually override the point value of a team in a game, only admins may do this.
+/// The history entry records the team, both point values and the reason.
+#[put("/outcomes/games/{id}/points")]
+async fn override_point_value(
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    req: HttpRequest,
+    game_id: web::Path<Uuid>,
+    point_override: web::Json<OverridePointValue>,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    let (previous, outcome) =
+        Outcome::override_point_value(*game_id, point_override.into_inner(), &pool).await?;
+
+    req.extensions_mut().insert(OperationDetail(format!(
+        "team {} from {} to {} ({})",
+        outcome.team_name,
+        previous.map_or("none".to_string(), |points| points.to_string()),
+        outcome.point_value.unwrap_or_default(),
+        outcome.override_reason.as_deref().unwrap_or_default()
+    )));
+    outcome.to_json()
 }
 
 #[get("/outcomes/teams/{id}")]
@@ -57,11 +145,68 @@ async fn find_all_outcomes_for_game(
     game_id: web::Path<Uuid>,
 ) -> ApiResult<impl Responder> {
     match auth.role {
-        UserRole::Admin => Outcome::find_all_for_game(*game_id, &pool).await?.to_json(),
+        UserRole::Admin => Outcome::find_all_for_game(*game_id, &mut *pool.acquire().await?)
+            .await?
+            .to_json(),
         UserRole::Referee => {
             // if the user is a referee, check if they are accessing the correct game
             if *game_id == User::find_game_for_ref(auth.id, &pool).await?.id {
-                Outcome::find_all_for_game(*game_id, &pool).await?.to_json()
+                Outcome::find_all_for_game(*game_id, &mut *pool.acquire().await?)
+                    .await?
+                    .to_json()
+            } else {
+                Err(CustomError::AccessDeniedError)
+            }
+        }
+        UserRole::Visualizer => Err(CustomError::AccessDeniedError),
+    }
+}
+
+#[get("/outcomes/games/{id}/attempts")]
+async fn find_all_attempts_for_game(
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    game_id: web::Path<Uuid>,
+) -> ApiResult<impl Responder> {
+    match auth.role {
+        UserRole::Admin => Attempt::find_all_for_game(*game_id, &pool).await?.to_json(),
+        UserRole::Referee => {
+            if *game_id == User::find_game_for_ref(auth.id, &pool).await?.id {
+                Attempt::find_all_for_game(*game_id, &pool).await?.to_json()
+            } else {
+                Err(CustomError::AccessDeniedError)
+            }
+        }
+        UserRole::Visualizer => Err(CustomError::AccessDeniedError),
+    }
+}
+
+/// Submit a single attempt for a game with several attempts, returns the team's outcome.
+/// Like for [update_outcome] the version of the outcome is taken from the `If-Match`-header if present, otherwise from the body.
+#[put("/outcomes/games/{id}/attempts")]
+async fn set_attempt(
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    req: HttpRequest,
+    game_id: web::Path<Uuid>,
+    attempt: web::Json<SetAttempt>,
+) -> ApiResult<impl Responder> {
+    let mut attempt = attempt.into_inner();
+    if let Some(version) = version_from_if_match(&req)? {
+        attempt.version = Some(version);
+    }
+
+    match auth.role {
+        UserRole::Admin => Attempt::set(*game_id, attempt, &auth.role, &pool)
+            .await?
+            .to_json(),
+        UserRole::Referee => {
+            let game = User::find_game_for_ref(auth.id, &pool).await?;
+
+            if *game_id == game.id {
+                Attempt::set(*game_id, attempt, &auth.role, &pool)
+                    .await?
+                    .to_json()
             } else {
                 Err(CustomError::AccessDeniedError)
             }
@@ -73,6 +218,10 @@ async fn find_all_outcomes_for_game(
 pub fn init(cfg: &mut web::ServiceConfig) {
     cfg.service(find_all_outcomes);
     cfg.service(update_outcome);
+    cfg.service(update_outcomes_for_game);
     cfg.service(find_all_outcomes_for_game);
+    cfg.service(find_all_attempts_for_game);
+    cfg.service(set_attempt);
+    cfg.service(override_point_value);
     cfg.service(find_all_outcomes_for_team);
 }
@@ -0,0 +1,106 @@
+use actix_web::{
+    HttpMessage, HttpRequest, Responder, delete, get, post, put,
+    web::{self, Data, Query},
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    ApiResult, ToJson,
+    middleware::{Authenticated, OperationDetail},
+    model::{CreatePointAdjustment, PointAdjustment, Team, UserRole, Year},
+};
+
+/// Describe the adjustment for the history, e.g. `team Adler: -5 (unsportsmanlike conduct)`.
+async fn describe(adjustment: &PointAdjustment, pool: &PgPool) -> ApiResult<String> {
+    let team = Team::find(adjustment.team_id, &mut *pool.acquire().await?).await?;
+    Ok(format!(
+        "team {}: {:+} ({})",
+        team.name, adjustment.amount, adjustment.reason
+    ))
+}
+
+#[get("/adjustments")]
+async fn find_all_adjustments(
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    year: Query<Year>,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin, UserRole::Visualizer])?;
+    PointAdjustment::find_all(**year, &pool).await?.to_json()
+}
+
+/// The history entry records the team, the amount and the reason, plus the ID since the path doesn't contain it.
+#[post("/adjustments")]
+async fn create_adjustment(
+    create_adjustment: web::Json<CreatePointAdjustment>,
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    req: HttpRequest,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    let adjustment = PointAdjustment::create(create_adjustment.into_inner(), &pool).await?;
+
+    let detail = format!(
+        "{} as {}",
+        describe(&adjustment, &pool).await?,
+        adjustment.id
+    );
+    req.extensions_mut().insert(OperationDetail(detail));
+    adjustment.to_json()
+}
+
+#[get("/adjustments/{id}")]
+async fn find_adjustment(
+    id: web::Path<Uuid>,
+    pool: Data<PgPool>,
+    auth: Authenticated,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin, UserRole::Visualizer])?;
+    PointAdjustment::find(*id, &pool).await?.to_json()
+}
+
+/// The history entry records the adjustment before and after the update.
+#[put("/adjustments/{id}")]
+async fn update_adjustment(
+    id: web::Path<Uuid>,
+    adjustment: web::Json<CreatePointAdjustment>,
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    req: HttpRequest,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    let (previous, adjustment) =
+        PointAdjustment::update(*id, adjustment.into_inner(), &pool).await?;
+
+    let detail = format!(
+        "from {} to {}",
+        describe(&previous, &pool).await?,
+        describe(&adjustment, &pool).await?
+    );
+    req.extensions_mut().insert(OperationDetail(detail));
+    adjustment.to_json()
+}
+
+#[delete("/adjustments/{id}")]
+async fn delete_adjustment(
+    id: web::Path<Uuid>,
+    pool: Data<PgPool>,
+    auth: Authenticated,
+    req: HttpRequest,
+) -> ApiResult<impl Responder> {
+    auth.has_roles(vec![UserRole::Admin])?;
+    let adjustment = PointAdjustment::delete(*id, &pool).await?;
+
+    let detail = describe(&adjustment, &pool).await?;
+    req.extensions_mut().insert(OperationDetail(detail));
+    adjustment.to_json()
+}
+
+pub fn init(cfg: &mut web::ServiceConfig) {
+    cfg.service(find_all_adjustments);
+    cfg.service(create_adjustment);
+    cfg.service(find_adjustment);
+    cfg.service(update_adjustment);
+    cfg.service(delete_adjustment);
+}
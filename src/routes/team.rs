@@ -50,7 +50,9 @@ async fn find_team(
     auth: Authenticated,
 ) -> ApiResult<impl Responder> {
     auth.has_roles(vec![UserRole::Admin, UserRole::Visualizer])?;
-    Team::find(*id, &pool).await?.to_json()
+    Team::find(*id, &mut *pool.acquire().await?)
+        .await?
+        .to_json()
 }
 
 #[put("/teams/{id}")]
@@ -0,0 +1,195 @@
+use crate::model::{Direction, ScoringKind, TrophyConfig, Value};
+
+/// Points for the first ten places, taken from Formula 1.
+const FORMULA_POINTS: [i32; 10] = [25, 18, 15, 12, 10, 8, 6, 4, 2, 1];
+
+/// Points for gold, silver and bronze.
+const PODIUM_POINTS: [i32; 3] = [3, 2, 1];
+
+/// Everything a [ScoringStrategy] may base its points on.
+pub struct Placement<'a> {
+    /// The 1-based place - outcomes with equal values share a place and the following places are skipped.
+    pub place: i32,
+    /// The amount of outcomes that are ranked together.
+    pub participants: i32,
+    /// Missing for teams that didn't finish, which are placed after all others.
+    pub value: Option<&'a Value>,
+    /// The value of the winning outcome, missing if nobody finished.
+    pub best: Option<&'a Value>,
+}
+
+/// Assigns points to a single, already ranked outcome.
+/// If [ScoringStrategy::floored], the result is raised to [TrophyConfig::min_points] by the caller, so strategies don't have to care about the floor.
+pub trait ScoringStrategy {
+    fn points(&self, placement: &Placement, config: &TrophyConfig) -> i32;
+
+    /// Whether no outcome receives less than [TrophyConfig::min_points].
+    /// Fixed tables like [Formula] and [Podium] already decide who gets nothing, so they aren't floored.
+    fn floored(&self) -> bool {
+        true
+    }
+}
+
+/// Start at [TrophyConfig::max_points] and subtract one per place - this is the original Klostertrophy-scheme.
+pub struct Descending;
+
+/// Like [Descending], but start at the number of participants so the last place always gets one point.
+pub struct Linear;
+
+/// Use [FORMULA_POINTS] for the first ten places, everybody else gets nothing.
+pub struct Formula;
+
+/// Use [PODIUM_POINTS] for the first three places, everybody else gets nothing.
+pub struct Podium;
+
+/// Award [TrophyConfig::max_points] multiplied by the ratio between an outcome and the winning outcome.
+pub struct Percentage;
+
+impl ScoringStrategy for Descending {
+    fn points(&self, placement: &Placement, config: &TrophyConfig) -> i32 {
+        config.max_points - (placement.place - 1)
+    }
+}
+
+impl ScoringStrategy for Linear {
+    fn points(&self, placement: &Placement, _config: &TrophyConfig) -> i32 {
+        placement.participants - (placement.place - 1)
+    }
+}
+
+impl ScoringStrategy for Formula {
+    fn points(&self, placement: &Placement, _config: &TrophyConfig) -> i32 {
+        table_points(&FORMULA_POINTS, placement.place)
+    }
+
+    fn floored(&self) -> bool {
+        false
+    }
+}
+
+impl ScoringStrategy for Podium {
+    fn points(&self, placement: &Placement, _config: &TrophyConfig) -> i32 {
+        table_points(&PODIUM_POINTS, placement.place)
+    }
+
+    fn floored(&self) -> bool {
+        false
+    }
+}
+
+impl ScoringStrategy for Percentage {
+    fn points(&self, placement: &Placement, config: &TrophyConfig) -> i32 {
+        // without a value, there's nothing to compare with the best one
+        let (Some(value), Some(best)) = (placement.value, placement.best) else {
+            return 0;
+        };
+
+        // smaller is better for some kinds, so the ratio has to be inverted for them
+        let ratio = match best.direction() {
+            Direction::HigherWins => value.as_f64() / best.as_f64(),
+            Direction::LowerWins => best.as_f64() / value.as_f64(),
+        };
+
+        // NaN only happens for 0/0, which means the outcome is as good as the best one
+        let ratio = if ratio.is_nan() { 1.0 } else { ratio };
+        (config.max_points as f64 * ratio.clamp(0.0, 1.0)).round() as i32
+    }
+}
+
+fn table_points(table: &[i32], place: i32) -> i32 {
+    table.get((place - 1) as usize).copied().unwrap_or(0)
+}
+
+impl ScoringKind {
+    /// Get the [ScoringStrategy] this kind stands for.
+    pub fn strategy(&self) -> &'static dyn ScoringStrategy {
+        match self {
+            ScoringKind::Descending => &Descending,
+            ScoringKind::Linear => &Linear,
+            ScoringKind::Formula => &Formula,
+            ScoringKind::Podium => &Podium,
+            ScoringKind::Percentage => &Percentage,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::MAX_POINTS;
+    use std::time::Duration;
+
+    fn config() -> TrophyConfig {
+        TrophyConfig::default_for(2024)
+    }
+
+    fn placement<'a>(place: i32, value: &'a Value, best: &'a Value) -> Placement<'a> {
+        Placement {
+            place,
+            participants: 12,
+            value: Some(value),
+            best: Some(best),
+        }
+    }
+
+    #[test]
+    fn linear_starts_at_participants() {
+        let value = Value::Points(1);
+        assert_eq!(Linear.points(&placement(1, &value, &value), &config()), 12);
+        assert_eq!(Linear.points(&placement(12, &value, &value), &config()), 1);
+    }
+
+    #[test]
+    fn formula_uses_table() {
+        let value = Value::Points(1);
+        assert_eq!(Formula.points(&placement(1, &value, &value), &config()), 25);
+        assert_eq!(Formula.points(&placement(10, &value, &value), &config()), 1);
+        assert_eq!(Formula.points(&placement(11, &value, &value), &config()), 0);
+    }
+
+    #[test]
+    fn podium_uses_table() {
+        let value = Value::Points(1);
+        assert_eq!(Podium.points(&placement(1, &value, &value), &config()), 3);
+        assert_eq!(Podium.points(&placement(3, &value, &value), &config()), 1);
+        assert_eq!(Podium.points(&placement(4, &value, &value), &config()), 0);
+    }
+
+    #[test]
+    fn percentage_of_points() {
+        let best = Value::Points(80);
+        let value = Value::Points(40);
+        assert_eq!(
+            Percentage.points(&placement(1, &best, &best), &config()),
+            MAX_POINTS
+        );
+        assert_eq!(
+            Percentage.points(&placement(2, &value, &best), &config()),
+            MAX_POINTS / 2
+        );
+    }
+
+    #[test]
+    fn percentage_of_time() {
+        let best = Value::Seconds(Duration::from_secs(60));
+        let value = Value::Seconds(Duration::from_secs(120));
+        assert_eq!(
+            Percentage.points(&placement(1, &best, &best), &config()),
+            MAX_POINTS
+        );
+        assert_eq!(
+            Percentage.points(&placement(2, &value, &best), &config()),
+            MAX_POINTS / 2
+        );
+    }
+
+    #[test]
+    fn percentage_of_lowest_points() {
+        let best = Value::LowestPoints(3);
+        let value = Value::LowestPoints(6);
+        assert_eq!(
+            Percentage.points(&placement(2, &value, &best), &config()),
+            MAX_POINTS / 2
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use crate::model::{
+    AttemptAggregation, CreateGame, CreateTeam, Game, GameKind, ScoringKind, Team, TeamGender,
+};
+
+/// Tests run in parallel within one process, so every one of them gets a year of its own.
+static NEXT_YEAR: AtomicI32 = AtomicI32::new(9000);
+
+/// A year nobody plays in for tests that need a database with the current schema at `DATABASE_URL`.
+/// These tests are ignored by default, run them with `cargo test -- --ignored`.
+pub struct TestYear {
+    pub pool: PgPool,
+    pub year: i32,
+}
+
+impl TestYear {
+    pub async fn new() -> TestYear {
+        dotenv::dotenv().ok();
+        let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+        let test_year = TestYear {
+            pool,
+            year: NEXT_YEAR.fetch_add(1, Ordering::SeqCst),
+        };
+        // an earlier run may have stopped before cleaning up
+        test_year.delete_all().await;
+
+        test_year
+    }
+
+    /// A game of points with a single attempt and descending scoring, change it as needed before [TestYear::game].
+    pub fn create_game(&self, trophy_id: i32, name: &str) -> CreateGame {
+        CreateGame {
+            trophy_id,
+            name: name.to_string(),
+            kind: GameKind::Points,
+            scoring: ScoringKind::Descending,
+            attempts: 1,
+            aggregation: AttemptAggregation::Sum,
+            year: self.year,
+        }
+    }
+
+    pub async fn game(&self, game: CreateGame) -> Game {
+        Game::create(game, &self.pool).await.unwrap()
+    }
+
+    /// A team in the category of its gender.
+    pub async fn team(&self, trophy_id: i32, gender: TeamGender) -> Team {
+        let team = CreateTeam {
+            trophy_id,
+            name: format!("Team {}", trophy_id),
+            gender,
+            category_id: None,
+            year: self.year,
+        };
+        Team::create(team, &self.pool).await.unwrap()
+    }
+
+    /// Store the data without any validation, like old data or a client that bypasses the API would.
+    pub async fn set_data(&self, game: &Game, team: &Team, data: &str) {
+        sqlx::query("UPDATE game_team SET data = $1 WHERE game_id = $2 AND team_id = $3")
+            .bind(data)
+            .bind(game.id)
+            .bind(team.id)
+            .execute(&self.pool)
+            .await
+            .unwrap();
+    }
+
+    pub async fn close_games(&self) {
+        sqlx::query("UPDATE games SET closed = true WHERE year = $1")
+            .bind(self.year)
+            .execute(&self.pool)
+            .await
+            .unwrap();
+    }
+
+    /// The stored totals of all teams, ordered by their trophy-id.
+    pub async fn points(&self) -> Vec<i32> {
+        sqlx::query_scalar("SELECT points FROM teams WHERE year = $1 ORDER BY trophy_id")
+            .bind(self.year)
+            .fetch_all(&self.pool)
+            .await
+            .unwrap()
+    }
+
+    /// Remove everything of the year, call this before asserting since a failed assertion would leave the data behind otherwise.
+    pub async fn clean_up(self) {
+        self.delete_all().await;
+    }
+
+    async fn delete_all(&self) {
+        for statement in [
+            "DELETE FROM transaction_history WHERE user_id IN (SELECT users.id FROM users INNER JOIN games ON users.game_id = games.id WHERE games.year = $1)",
+            "DELETE FROM users WHERE game_id IN (SELECT id FROM games WHERE year = $1)",
+            "DELETE FROM games WHERE year = $1",
+            "DELETE FROM teams WHERE year = $1",
+            "DELETE FROM categories WHERE year = $1",
+            "DELETE FROM trophy_config WHERE year = $1",
+        ] {
+            sqlx::query(statement)
+                .bind(self.year)
+                .execute(&self.pool)
+                .await
+                .unwrap();
+        }
+    }
+}